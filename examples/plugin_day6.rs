@@ -0,0 +1,82 @@
+//! An example solver plugin, solving day 6 part 2 in closed form rather than trying every hold time.
+//!
+//! Build it with `cargo build --release --example plugin_day6`, then copy
+//! `target/release/examples/libplugin_day6.so` into `./plugins`.
+
+use std::ffi::{c_char, c_void, CString};
+
+type PluginSolveFn = unsafe extern "C" fn(input: *const u8, len: usize) -> *mut c_char;
+type RegisterFn = unsafe extern "C" fn(ctx: *mut c_void, day: u8, part: u8, name: *const c_char, solve: PluginSolveFn);
+
+#[repr(C)]
+pub struct AocRegistrar {
+    ctx: *mut c_void,
+    register: RegisterFn,
+}
+
+#[no_mangle]
+pub extern "C" fn aoc_plugin_abi_version() -> u32 {
+    1
+}
+
+/// # Safety
+/// `registrar` must be the pointer handed to the plugin by the runner.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_plugin_register(registrar: *mut AocRegistrar) {
+    let registrar = &*registrar;
+    (registrar.register)(registrar.ctx, 6, 2, c"closed_form".as_ptr(), solve_day6_part2);
+}
+
+/// # Safety
+/// `answer` must have been returned by one of this plugin's solvers.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_plugin_free_answer(answer: *mut c_char) {
+    drop(CString::from_raw(answer));
+}
+
+unsafe extern "C" fn solve_day6_part2(input: *const u8, len: usize) -> *mut c_char {
+    let Ok(input) = std::str::from_utf8(std::slice::from_raw_parts(input, len)) else { return std::ptr::null_mut() };
+
+    match ways_to_win(input) {
+        Some(ways) => CString::new(ways.to_string()).unwrap().into_raw(),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Holding for `h` of `t` milliseconds travels `h * (t - h)`, so the hold times that reach the
+/// record are the integers between the roots of `h^2 - t*h + record = 0`. Like the built-in solver,
+/// it counts the holds in `0..t` that travel at least the record.
+fn ways_to_win(input: &str) -> Option<u64> {
+    let mut lines = input.lines();
+    let time = combined_number(lines.next()?.strip_prefix("Time:")?)?;
+    let record = combined_number(lines.next()?.strip_prefix("Distance:")?)?;
+
+    let Some(discriminant) = (time * time).checked_sub(4 * record) else { return Some(0) };
+    if time == 0 {
+        return Some(0);
+    }
+
+    // The roots are only estimates in floating point, so settle each end by checking holds exactly.
+    let reaches = |hold: u64| hold * (time - hold) >= record;
+    let root = (discriminant as f64).sqrt();
+    let mut low = ((time as f64 - root) / 2.0).ceil() as u64;
+    let mut high = (((time as f64 + root) / 2.0).floor() as u64).min(time - 1);
+    while low > 0 && reaches(low - 1) {
+        low -= 1;
+    }
+    while low <= high && !reaches(low) {
+        low += 1;
+    }
+    while high + 1 < time && reaches(high + 1) {
+        high += 1;
+    }
+    while high > low && !reaches(high) {
+        high -= 1;
+    }
+
+    Some(if low > high { 0 } else { high - low + 1 })
+}
+
+fn combined_number(i: &str) -> Option<u64> {
+    i.chars().filter(|c| !c.is_whitespace()).collect::<String>().parse().ok()
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::Duration;
+use crate::solutions::{Outcome, Solver};
+use crate::utilities::fingerprint;
+
+pub(crate) const DEFAULT_CACHE_PATH: &str = ".answer_cache";
+
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+struct CacheKey {
+    day: u8,
+    part: u8,
+    input_hash: u64,
+    solver_hash: u64,
+}
+
+impl CacheKey {
+    fn new(solver: &Solver, i: &str) -> Self {
+        Self {
+            day: solver.day,
+            part: solver.part,
+            input_hash: fingerprint(i.as_bytes()),
+            // Variants of the same day share a source file, so the name keeps their answers apart.
+            solver_hash: fingerprint(format!("{}:{:016x}", solver.name, solver.version).as_bytes()),
+        }
+    }
+}
+
+/// On-disk store of previously computed answers, one per line as
+/// `<day> <part> <input hash> <solver hash> <micros> <answer>`.
+pub(crate) struct AnswerCache {
+    path: String,
+    entries: HashMap<CacheKey, (String, Duration)>,
+}
+
+impl AnswerCache {
+    pub fn load(path: &str) -> Self {
+        let entries = fs::read_to_string(path)
+            .map(|contents| contents.lines().filter_map(parse_entry).collect())
+            .unwrap_or_default();
+
+        Self {
+            path: path.to_owned(),
+            entries,
+        }
+    }
+
+    /// Returns the cached outcome when this solver has already seen this exact input,
+    /// otherwise runs the solver and records the result.
+    pub fn run(&mut self, solver: &Solver, i: &str) -> Outcome {
+        let key = CacheKey::new(solver, i);
+        if let Some((answer, elapsed)) = self.entries.get(&key) {
+            return Outcome { answer: answer.clone(), elapsed: *elapsed, cached: true };
+        }
+
+        let outcome = solver.run(i);
+        self.insert(key, &outcome);
+        outcome
+    }
+
+    fn insert(&mut self, key: CacheKey, outcome: &Outcome) {
+        self.entries.insert(key, (outcome.answer.clone(), outcome.elapsed));
+
+        let line = format!(
+            "{} {} {:016x} {:016x} {} {}",
+            key.day, key.part, key.input_hash, key.solver_hash, outcome.elapsed.as_micros(), outcome.answer
+        );
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&self.path) {
+            _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+/// Runs `solver`, going through `cache` unless caching has been turned off.
+pub(crate) fn run_solver(cache: &mut Option<AnswerCache>, solver: &Solver, i: &str) -> Outcome {
+    match cache {
+        Some(cache) => cache.run(solver, i),
+        None => solver.run(i),
+    }
+}
+
+fn parse_entry(line: &str) -> Option<(CacheKey, (String, Duration))> {
+    let mut fields = line.splitn(6, ' ');
+    let key = CacheKey {
+        day: fields.next()?.parse().ok()?,
+        part: fields.next()?.parse().ok()?,
+        input_hash: u64::from_str_radix(fields.next()?, 16).ok()?,
+        solver_hash: u64::from_str_radix(fields.next()?, 16).ok()?,
+    };
+    let elapsed = Duration::from_micros(fields.next()?.parse().ok()?);
+    let answer = fields.next()?.to_owned();
+
+    Some((key, (answer, elapsed)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_cache_entry() {
+        let (key, (answer, elapsed)) = parse_entry("5 2 00000000000000ff 0000000000000001 1500 125742456").unwrap();
+
+        assert_eq!(key, CacheKey { day: 5, part: 2, input_hash: 255, solver_hash: 1 });
+        assert_eq!(answer, "125742456");
+        assert_eq!(elapsed, Duration::from_micros(1500));
+    }
+
+    #[test]
+    fn should_only_hit_cache_for_same_input_and_source() {
+        let solver = Solver::builtin(6, 1, "v1", |i| i.len().to_string());
+        let changed = Solver::builtin(6, 1, "v2", |i| i.len().to_string());
+        let mut cache = AnswerCache { path: String::new(), entries: HashMap::new() };
+
+        assert!(!cache.run(&solver, "abc").cached);
+        assert!(cache.run(&solver, "abc").cached);
+        assert!(!cache.run(&solver, "abcd").cached);
+        assert!(!cache.run(&changed, "abc").cached);
+    }
+
+    #[test]
+    fn should_keep_variants_of_the_same_source_apart() {
+        let solver = Solver::builtin(6, 1, "v1", |i| i.len().to_string());
+        let variant = Solver::builtin(6, 1, "v1", |_| "0".to_owned()).named("zero");
+        let mut cache = AnswerCache { path: String::new(), entries: HashMap::new() };
+
+        assert_eq!(cache.run(&solver, "abc").answer, "3");
+        assert_eq!(cache.run(&variant, "abc").answer, "0");
+    }
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+use crate::cache::{run_solver, AnswerCache};
+use crate::solutions::{Outcome, Solver};
+
+/// Runs every variant registered for `day` (optionally just `part`) on the same input, printing their
+/// answers and timings relative to the fastest. Returns whether all variants of each part agreed.
+pub(crate) fn compare(solvers: &[Solver], day: u8, part: Option<u8>, i: &str, cache: &mut Option<AnswerCache>) -> bool {
+    let mut parts = solvers.iter()
+        .filter(|s| s.day == day && part.is_none_or(|p| s.part == p))
+        .map(|s| s.part)
+        .collect::<Vec<u8>>();
+    parts.sort();
+    parts.dedup();
+
+    if parts.is_empty() {
+        println!("No solvers registered for day {}", day);
+        return false;
+    }
+
+    let mut all_agree = true;
+    for part in parts {
+        let variants = solvers.iter().filter(|s| s.day == day && s.part == part).collect::<Vec<&Solver>>();
+        let outcomes = variants.iter().map(|s| run_solver(cache, s, i)).collect::<Vec<Outcome>>();
+
+        println!("Day {} Part {}", day, part);
+        let fastest = outcomes.iter().map(|o| o.elapsed).min().unwrap_or_default();
+        let name_width = variants.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        for (solver, outcome) in variants.iter().zip(&outcomes) {
+            let cached = if outcome.cached { " (cached)" } else { "" };
+            println!(
+                "  {:<width$}  {:>16}  {:>12}  {:>9}{}",
+                solver.name,
+                outcome.answer,
+                format!("{:.2?}", outcome.elapsed),
+                format!("{:.2}x", relative(outcome.elapsed, fastest)),
+                cached,
+                width = name_width
+            );
+        }
+
+        if outcomes.len() == 1 {
+            println!("  ➖ only one variant registered");
+        } else if agree(&outcomes) {
+            println!("  ✅ all {} variants agree", outcomes.len());
+        } else {
+            println!("  ❌ variants disagree");
+            all_agree = false;
+        }
+    }
+
+    all_agree
+}
+
+fn agree(outcomes: &[Outcome]) -> bool {
+    outcomes.windows(2).all(|w| w[0].answer == w[1].answer)
+}
+
+fn relative(elapsed: Duration, fastest: Duration) -> f64 {
+    if fastest.is_zero() { 1.0 } else { elapsed.as_secs_f64() / fastest.as_secs_f64() }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_agree_when_all_answers_match() {
+        let solvers = vec![
+            Solver::builtin(6, 1, "", |i| i.len().to_string()),
+            Solver::builtin(6, 1, "", |i| i.chars().count().to_string()).named("chars"),
+        ];
+
+        assert!(compare(&solvers, 6, None, "abc", &mut None));
+    }
+
+    #[test]
+    fn should_disagree_when_any_answer_differs() {
+        let solvers = vec![
+            Solver::builtin(6, 1, "", |i| i.len().to_string()),
+            Solver::builtin(6, 2, "", |i| i.len().to_string()),
+            Solver::builtin(6, 2, "", |_| "0".to_owned()).named("zero"),
+        ];
+
+        assert!(compare(&solvers, 6, Some(1), "abc", &mut None));
+        assert!(!compare(&solvers, 6, None, "abc", &mut None));
+    }
+
+    #[test]
+    fn should_report_missing_day() {
+        assert!(!compare(&[], 9, None, "", &mut None));
+    }
+
+    #[test]
+    fn relative_timing_should_be_against_fastest() {
+        assert_eq!(relative(Duration::from_millis(30), Duration::from_millis(10)), 3.0);
+        assert_eq!(relative(Duration::ZERO, Duration::ZERO), 1.0);
+    }
+}
@@ -0,0 +1,122 @@
+//! Differential tests: random but puzzle-shaped inputs are fed to both a deliberately naive reference
+//! implementation and the real solver. Any disagreement is shrunk by proptest to a minimal failing input.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use proptest::prelude::*;
+use crate::solutions::{day4, day5, day7};
+use crate::solutions::day7::{get_standard_type, get_type, Card};
+
+const CATEGORIES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+const CARDS: &str = "23456789TJQKA";
+
+/// Scratchcards as the number of matches on each card, never winning copies past the last card.
+fn scratchcards() -> impl Strategy<Value = Vec<usize>> {
+    prop::collection::vec(0usize..=4, 1..12).prop_map(|matches| {
+        let count = matches.len();
+        matches.into_iter().enumerate().map(|(idx, m)| m.min(count - idx - 1)).collect()
+    })
+}
+
+fn render_scratchcards(matches: &[usize]) -> String {
+    matches.iter().enumerate().map(|(idx, m)| {
+        let winning = (10..15).collect::<Vec<u32>>();
+        let mine = winning[..*m].iter().copied().chain(50..(58 - *m as u32)).collect::<Vec<u32>>();
+        format!("Card {:>3}: {} | {}", idx + 1, join(&winning), join(&mine))
+    }).collect::<Vec<String>>().join("\n")
+}
+
+/// Plays out the copy cascade one physical card at a time.
+fn reference_card_count(matches: &[usize]) -> u64 {
+    let mut pending = (0..matches.len()).collect::<Vec<usize>>();
+    let mut count = 0;
+    while let Some(card) = pending.pop() {
+        count += 1;
+        pending.extend(card + 1..=card + matches[card]);
+    }
+
+    count
+}
+
+/// A conversion map as `(destination, source, length)` rows with non-overlapping sources.
+fn conversion_map() -> impl Strategy<Value = Vec<(u64, u64, u64)>> {
+    prop::collection::vec((0u64..10, 1u64..15, 0u64..100), 1..4).prop_map(|rows| {
+        let mut source = 0;
+        rows.into_iter().map(|(gap, length, destination)| {
+            source += gap;
+            let row = (destination, source, length);
+            source += length;
+            row
+        }).collect()
+    })
+}
+
+/// Seed ranges as `(start, length)` and the conversion maps between each pair of categories.
+type Almanac = (Vec<(u64, u64)>, Vec<Vec<(u64, u64, u64)>>);
+
+fn almanac() -> impl Strategy<Value = Almanac> {
+    (
+        prop::collection::vec((0u64..100, 1u64..20), 1..4),
+        prop::collection::vec(conversion_map(), CATEGORIES.len() - 1),
+    )
+}
+
+fn render_almanac(seeds: &[(u64, u64)], maps: &[Vec<(u64, u64, u64)>]) -> String {
+    let seeds = seeds.iter().flat_map(|(start, length)| [*start, *length]).collect::<Vec<u64>>();
+    let maps = maps.iter().enumerate().map(|(idx, rows)| {
+        let rows = rows.iter().map(|(d, s, l)| format!("{} {} {}", d, s, l)).collect::<Vec<String>>().join("\n");
+        format!("{}-to-{} map:\n{}", CATEGORIES[idx], CATEGORIES[idx + 1], rows)
+    }).collect::<Vec<String>>().join("\n\n");
+
+    format!("seeds: {}\n\n{}", join(&seeds), maps)
+}
+
+fn hand() -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(CARDS.chars().collect::<Vec<char>>()), 5)
+        .prop_map(|cards| cards.into_iter().collect())
+}
+
+fn card_frequencies(hand: &str) -> BTreeMap<Card, u32> {
+    hand.chars().fold(BTreeMap::new(), |mut acc, c| {
+        *acc.entry(Card::from_str(&c.to_string()).unwrap()).or_insert(0) += 1;
+        acc
+    })
+}
+
+/// Tries every card a joker could stand in for and keeps the strongest resulting hand.
+fn reference_joker_type(hand: &str) -> day7::HandType {
+    CARDS.chars()
+        .filter(|c| *c != 'J')
+        .map(|c| get_standard_type(card_frequencies(&hand.replace('J', &c.to_string()))))
+        .max()
+        .unwrap()
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+proptest! {
+    #[test]
+    fn day4_copy_cascade_matches_reference(matches in scratchcards()) {
+        let input = render_scratchcards(&matches);
+        prop_assert_eq!(day4::part2(&input), reference_card_count(&matches), "input:\n{}", input);
+    }
+
+    #[test]
+    fn day5_range_conversion_matches_brute_force((seeds, maps) in almanac()) {
+        let input = render_almanac(&seeds, &maps);
+        prop_assert_eq!(day5::part2(&input), day5::part2_brute_force(&input), "input:\n{}", input);
+    }
+
+    #[test]
+    fn day7_joker_type_matches_reference(hand in hand()) {
+        prop_assert_eq!(get_type(card_frequencies(&hand)), reference_joker_type(&hand), "hand: {}", hand);
+    }
+}
+
+#[test]
+fn rendered_inputs_should_parse_as_puzzle_inputs() {
+    assert_eq!(day4::part1(&render_scratchcards(&[2, 1, 0])), 3);
+    assert_eq!(day5::part1(&render_almanac(&[(5, 1)], &vec![vec![(0, 100, 1)]; 7])), 1);
+}
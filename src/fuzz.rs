@@ -0,0 +1,130 @@
+//! Fuzzing entry points for every day's parser. Each target takes arbitrary bytes and must return
+//! (with an error, if the input is malformed) rather than panic.
+//!
+//! `aoc2023 fuzz --day N` feeds stdin to a target so external fuzzers such as AFL can drive the
+//! binary directly, and the tests below throw random and puzzle-shaped input at every target.
+
+use crate::solutions::{day1, day2, day3, day4, day5, day6, day7, day8};
+
+pub(crate) fn target(day: u8) -> Option<fn(&[u8])> {
+    match day {
+        1 => Some(fuzz_day1),
+        2 => Some(fuzz_day2),
+        3 => Some(fuzz_day3),
+        4 => Some(fuzz_day4),
+        5 => Some(fuzz_day5),
+        6 => Some(fuzz_day6),
+        7 => Some(fuzz_day7),
+        8 => Some(fuzz_day8),
+        _ => None,
+    }
+}
+
+fn fuzz_day1(data: &[u8]) {
+    let patterns = day1::CalibrationPatterns::digits_and_words();
+    let numbers = day1::CalibrationPatterns::with_words(&day1::Vocabulary::english(), false).with_extraction(day1::Extraction::SumOfNumbers);
+    for line in String::from_utf8_lossy(data).lines() {
+        _ = day1::parse_calibration_string(&patterns, line);
+        _ = day1::parse_calibration_string(&numbers, line);
+    }
+}
+
+fn fuzz_day2(data: &[u8]) {
+    let mut colours = day2::Colours::new();
+    for line in String::from_utf8_lossy(data).lines() {
+        _ = day2::GameData::parse(line, &mut colours);
+    }
+}
+
+fn fuzz_day3(data: &[u8]) {
+    if let Some(schematic) = day3::parse_schematic(&String::from_utf8_lossy(data)) {
+        _ = day3::numbers_around_symbols(&schematic);
+    }
+}
+
+fn fuzz_day4(data: &[u8]) {
+    for line in String::from_utf8_lossy(data).lines() {
+        _ = day4::get_winning_numbers(line);
+    }
+}
+
+fn fuzz_day5(data: &[u8]) {
+    _ = day5::parse_full_conversion_map_file(&String::from_utf8_lossy(data));
+}
+
+fn fuzz_day6(data: &[u8]) {
+    let i = String::from_utf8_lossy(data);
+    _ = day6::parse_race_data(&i);
+    _ = day6::parse_race_data_combined(&i);
+}
+
+fn fuzz_day7(data: &[u8]) {
+    _ = day7::parse_hands(&String::from_utf8_lossy(data));
+}
+
+fn fuzz_day8(data: &[u8]) {
+    _ = day8::parse_map_file(&String::from_utf8_lossy(data));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn targets_should_survive_arbitrary_bytes(day in 1u8..=8, data in prop::collection::vec(any::<u8>(), 0..512)) {
+            target(day).unwrap()(&data);
+        }
+
+        #[test]
+        fn day1_should_survive_calibration_like_input(i in "[a-z0-9\n]{0,200}") {
+            fuzz_day1(i.as_bytes());
+        }
+
+        #[test]
+        fn day2_should_survive_game_like_input(i in "(Game [0-9]{0,3}(: ?)?(([0-9]{0,2} ?(red|green|blue)?)(, |; )?){0,6}\n?){0,5}") {
+            fuzz_day2(i.as_bytes());
+        }
+
+        #[test]
+        fn day3_should_survive_schematic_like_input(rows in prop::collection::vec("[0-9.*#$]{6}", 0..8)) {
+            fuzz_day3(rows.join("\n").as_bytes());
+        }
+
+        #[test]
+        fn day4_should_survive_card_like_input(i in "(Card [0-9 ]{0,3}(: )?[0-9 ]{0,12}( \\| )?[0-9 x]{0,12}\n?){0,5}") {
+            fuzz_day4(i.as_bytes());
+        }
+
+        #[test]
+        fn day5_should_survive_almanac_like_input(i in "seeds: [0-9 ]{0,20}\n\n([a-z]{2,8}-to-[a-z]{2,8} map:\n([0-9]{1,21} [0-9]{1,21} [0-9]{1,21}\n?){0,3}\n?){0,4}") {
+            fuzz_day5(i.as_bytes());
+        }
+
+        #[test]
+        fn day6_should_survive_race_like_input(i in "Time: [0-9 ]{0,40}\nDistance: [0-9 ]{0,40}") {
+            fuzz_day6(i.as_bytes());
+        }
+
+        #[test]
+        fn day7_should_survive_hand_like_input(i in "([2-9TJQKA]{0,7} ?[0-9]{0,12}\n?){0,6}") {
+            fuzz_day7(i.as_bytes());
+        }
+
+        #[test]
+        fn day8_should_survive_network_like_input(i in "[LR]{0,6}\n\n([A-Z0-9]{0,4} = \\(?[A-Z0-9]{0,3}, [A-Z0-9]{0,3}\\)?\n?){0,5}") {
+            fuzz_day8(i.as_bytes());
+        }
+    }
+
+    #[test]
+    fn should_not_panic_on_known_crashes() {
+        fuzz_day2(b"Game 1: 3");
+        fuzz_day3(b"5.\n.*");
+        fuzz_day3(b"1*\n..");
+        fuzz_day5(b"seeds: 1\n\nseed-to-fuel map:\n1 2 3");
+        fuzz_day6(b"Time: 99999999999 99999999999\nDistance: 1");
+        fuzz_day7(b"AAAAAAAA 1");
+    }
+}
@@ -0,0 +1,278 @@
+//! Random but puzzle-shaped inputs for every implemented day, for load testing the solvers.
+//!
+//! The same `--seed` always produces the same input. What `--size` controls depends on the day:
+//! lines for day 1, games for day 2, the width and height of the schematic for day 3, cards for
+//! day 4, seed ranges (and map rows) for day 5, races for day 6 (at most four, like the real sheet),
+//! hands for day 7 and nodes for day 8.
+
+use std::collections::VecDeque;
+
+const NUMBER_WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+const COLOURS: [&str; 3] = ["red", "green", "blue"];
+const SYMBOLS: &str = "*#+$/@=%-&";
+const CARDS: &str = "23456789TJQKA";
+const CATEGORIES: [&str; 8] = ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+const MAX_NODES: usize = 26 * 26 * 26;
+const MAX_CARD_COPIES: u64 = 1_000_000;
+/// The real race sheet has four races; with more, neither the product of ways to win nor the
+/// combined part 2 race fits the solvers' integer types.
+const MAX_RACES: usize = 4;
+
+/// The `--size` used when none is given.
+pub(crate) const DEFAULT_SIZE: usize = 1000;
+
+/// A small SplitMix64 generator, so inputs only depend on the seed and not on a dependency's version.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `low..high`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next_u64() % (high - low).max(1)
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0, len as u64) as usize
+    }
+
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.range(0, denominator) < numerator
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.index(i + 1));
+        }
+    }
+}
+
+pub(crate) fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    let mut rng = Rng::new(seed);
+    let size = size.max(1);
+
+    match day {
+        1 => Some(calibration_document(&mut rng, size)),
+        2 => Some(bag_games(&mut rng, size)),
+        3 => Some(engine_schematic(&mut rng, size)),
+        4 => Some(scratchcards(&mut rng, size)),
+        5 => Some(almanac(&mut rng, size)),
+        6 => Some(race_sheet(&mut rng, size.min(MAX_RACES))),
+        7 => Some(camel_card_hands(&mut rng, size)),
+        8 => Some(node_network(&mut rng, size)),
+        _ => None,
+    }
+}
+
+/// Lines of letters with digits and spelled-out digits mixed in, always containing at least one digit.
+fn calibration_document(rng: &mut Rng, lines: usize) -> String {
+    (0..lines).map(|_| {
+        let mut line = String::new();
+        let digit_at = rng.range(0, 6);
+        for piece in 0..rng.range(digit_at + 1, 8) {
+            match rng.range(0, 3) {
+                _ if piece == digit_at => line.push_str(&rng.range(1, 10).to_string()),
+                0 => line.push_str(NUMBER_WORDS[rng.index(NUMBER_WORDS.len())]),
+                1 => line.push_str(&rng.range(1, 10).to_string()),
+                _ => (0..rng.range(1, 5)).for_each(|_| line.push((b'a' + rng.range(0, 26) as u8) as char)),
+            }
+        }
+        line
+    }).collect::<Vec<String>>().join("\n")
+}
+
+fn bag_games(rng: &mut Rng, games: usize) -> String {
+    (1..=games).map(|id| {
+        let reveals = (0..rng.range(1, 7)).map(|_| {
+            let mut colours = COLOURS.to_vec();
+            rng.shuffle(&mut colours);
+            colours.truncate(rng.range(1, 4) as usize);
+            colours.iter().map(|c| format!("{} {}", rng.range(1, 21), c)).collect::<Vec<String>>().join(", ")
+        }).collect::<Vec<String>>();
+
+        format!("Game {}: {}", id, reveals.join("; "))
+    }).collect::<Vec<String>>().join("\n")
+}
+
+/// A square schematic with numbers of up to three digits, always separated by at least one `.`.
+fn engine_schematic(rng: &mut Rng, width: usize) -> String {
+    (0..width).map(|_| {
+        let mut row = String::new();
+        while row.len() < width {
+            let remaining = width - row.len();
+            match rng.range(0, 10) {
+                0..=1 if remaining > 1 => {
+                    let digits = rng.range(1, 4).min(remaining as u64 - 1) as u32;
+                    row.push_str(&rng.range(10u64.pow(digits - 1), 10u64.pow(digits)).to_string());
+                    row.push('.');
+                }
+                2 => row.push(SYMBOLS.as_bytes()[rng.index(SYMBOLS.len())] as char),
+                _ => row.push('.'),
+            }
+        }
+        row
+    }).collect::<Vec<String>>().join("\n")
+}
+
+/// Cards with 10 winning numbers and 25 of your own, never winning copies past the last card.
+/// Like the real input, most cards win nothing. A card with more than [`MAX_CARD_COPIES`] copies
+/// never wins, so the copies can't grow without bound however many cards there are.
+fn scratchcards(rng: &mut Rng, cards: usize) -> String {
+    let mut owed_copies: VecDeque<u64> = VecDeque::new();
+    (1..=cards).map(|id| {
+        let mut numbers = (1..100).collect::<Vec<u32>>();
+        rng.shuffle(&mut numbers);
+        let winning = &numbers[..10];
+
+        let copies = 1 + owed_copies.pop_front().unwrap_or(0);
+        let wins = rng.chance(1, 3) && copies <= MAX_CARD_COPIES;
+        let matches = if wins { rng.range(1, 11).min((cards - id) as u64) as usize } else { 0 };
+        if owed_copies.len() < matches {
+            owed_copies.resize(matches, 0);
+        }
+        owed_copies.iter_mut().take(matches).for_each(|owed| *owed += copies);
+
+        let mut mine = winning[..matches].to_vec();
+        mine.extend(&numbers[10..10 + 25 - matches]);
+        rng.shuffle(&mut mine);
+
+        format!("Card {:>3}: {} | {}", id, padded(winning), padded(&mine))
+    }).collect::<Vec<String>>().join("\n")
+}
+
+/// Seed ranges followed by the seven conversion maps, each with non-overlapping source ranges.
+fn almanac(rng: &mut Rng, seed_ranges: usize) -> String {
+    let seeds = (0..seed_ranges)
+        .map(|_| format!("{} {}", rng.range(0, 4_000_000_000), rng.range(1, 500_000_000)))
+        .collect::<Vec<String>>();
+
+    let maps = CATEGORIES.windows(2).map(|pair| {
+        let mut source = rng.range(0, 100_000_000);
+        let rows = (0..seed_ranges.max(3)).map(|_| {
+            let length = rng.range(1, 500_000_000);
+            let row = format!("{} {} {}", rng.range(0, 4_000_000_000), source, length);
+            source += length + rng.range(0, 50_000_000);
+            row
+        }).collect::<Vec<String>>();
+
+        format!("{}-to-{} map:\n{}", pair[0], pair[1], rows.join("\n"))
+    }).collect::<Vec<String>>();
+
+    format!("seeds: {}\n\n{}", seeds.join(" "), maps.join("\n\n"))
+}
+
+/// Races whose records can always be beaten.
+fn race_sheet(rng: &mut Rng, races: usize) -> String {
+    let (times, records): (Vec<u64>, Vec<u64>) = (0..races).map(|_| {
+        let time = rng.range(7, 100);
+        let best = (time / 2) * (time - time / 2);
+        (time, rng.range(best / 3, best))
+    }).unzip();
+
+    let column = |values: &Vec<u64>| values.iter().map(|v| format!("{:>5}", v)).collect::<String>();
+    format!("Time:    {}\nDistance:{}", column(&times), column(&records))
+}
+
+fn camel_card_hands(rng: &mut Rng, hands: usize) -> String {
+    (0..hands).map(|_| {
+        let hand = (0..5).map(|_| CARDS.as_bytes()[rng.index(CARDS.len())] as char).collect::<String>();
+        format!("{} {}", hand, rng.range(1, 1001))
+    }).collect::<Vec<String>>().join("\n")
+}
+
+/// A network where every edge leads further along a random ordering of the nodes, so any walk
+/// from `AAA` reaches `ZZZ`.
+fn node_network(rng: &mut Rng, nodes: usize) -> String {
+    let count = nodes.clamp(2, MAX_NODES);
+    let mut ids = (0..MAX_NODES).map(node_id).filter(|id| id != "AAA" && id != "ZZZ").collect::<Vec<String>>();
+    rng.shuffle(&mut ids);
+    ids.truncate(count - 2);
+    ids.insert(0, "AAA".to_owned());
+    ids.push("ZZZ".to_owned());
+
+    let directions = (0..rng.range(2, 300)).map(|_| if rng.chance(1, 2) { 'L' } else { 'R' }).collect::<String>();
+    let last = ids.len() - 1;
+    let network = ids.iter().enumerate().map(|(idx, id)| {
+        let (left, right) = if idx == last {
+            (last, last)
+        } else {
+            let hop = |rng: &mut Rng| rng.range(idx as u64 + 1, (idx + 4).min(last) as u64 + 1) as usize;
+            (hop(rng), hop(rng))
+        };
+        format!("{} = ({}, {})\n", id, ids[left], ids[right])
+    }).collect::<String>();
+
+    format!("{}\n\n{}", directions, network)
+}
+
+fn node_id(n: usize) -> String {
+    [n / 676, n / 26 % 26, n % 26].iter().map(|c| (b'A' + *c as u8) as char).collect()
+}
+
+fn padded(numbers: &[u32]) -> String {
+    numbers.iter().map(|n| format!("{:>2}", n)).collect::<Vec<String>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solutions::{primary, registry};
+
+    #[test]
+    fn should_generate_same_input_for_same_seed() {
+        for day in 1..=8 {
+            assert_eq!(generate(day, 7, 20), generate(day, 7, 20));
+            assert_ne!(generate(day, 7, 20), generate(day, 8, 20));
+        }
+        assert_eq!(generate(9, 7, 20), None);
+    }
+
+    #[test]
+    fn generated_inputs_should_be_solvable() {
+        let solvers = registry();
+        for seed in 0..5 {
+            for solver in primary(&solvers) {
+                let i = generate(solver.day, seed, 12).unwrap();
+                (solver.solve)(&i);
+            }
+        }
+    }
+
+    #[test]
+    fn generated_inputs_should_be_solvable_at_default_size() {
+        let solvers = registry();
+        for solver in primary(&solvers) {
+            let i = generate(solver.day, 0, DEFAULT_SIZE).unwrap();
+            (solver.solve)(&i);
+        }
+    }
+
+    #[test]
+    fn race_sheet_should_stop_at_four_races() {
+        let sheet = generate(6, 1, DEFAULT_SIZE).unwrap();
+        assert_eq!(sheet.lines().next().unwrap().split_whitespace().count(), 1 + MAX_RACES);
+    }
+
+    #[test]
+    fn engine_schematic_should_be_square() {
+        let schematic = generate(3, 1, 30).unwrap();
+        assert_eq!(schematic.lines().count(), 30);
+        assert!(schematic.lines().all(|l| l.len() == 30));
+    }
+
+    #[test]
+    fn node_ids_should_cover_three_letters() {
+        assert_eq!(node_id(0), "AAA");
+        assert_eq!(node_id(MAX_NODES - 1), "ZZZ");
+    }
+}
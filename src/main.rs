@@ -1,32 +1,350 @@
+mod cache;
+mod compare;
+#[cfg(test)]
+mod differential;
+mod fuzz;
+mod generate;
+mod plugins;
+mod query;
+mod report;
+mod seal;
+mod serve;
 mod solutions;
 mod utilities;
 
-use clap::Parser;
-use crate::solutions::{day1, day2, day3, day4, day5, day6, day7, day8};
+use std::io::Read;
+use std::time::Instant;
+use clap::{Parser, Subcommand};
+use crate::cache::{run_solver, AnswerCache, DEFAULT_CACHE_PATH};
+use crate::report::{ReportFormat, ReportOptions};
+use crate::seal::Secret;
+use crate::solutions::{day1, day2, primary, registry, Solver, BUILTIN};
+use crate::utilities::input::load_input;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long, required=true)]
-    day: u8,
+    day: Option<u8>,
 
     #[arg(short, long, required=true)]
-    input_path: String,
+    input_path: Option<String>,
+
+    /// Ask a question of the day 2 games instead of solving them, e.g. `sum(min_bag_power) where id < 50`
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// Always run the solvers instead of reusing answers from the cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Load extra solvers from the shared libraries in this directory
+    #[arg(long, global = true, default_value = "./plugins")]
+    plugins_dir: String,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run every day and write a report of answers, timings and history
+    Report {
+        #[arg(short, long, value_enum, default_value_t = ReportFormat::Markdown)]
+        format: ReportFormat,
+
+        #[arg(short, long, default_value = "report.md")]
+        output_path: String,
+
+        #[arg(long, default_value = "./inputs")]
+        inputs_dir: String,
+
+        #[arg(long, default_value = "answers.txt")]
+        answers_path: String,
+
+        #[arg(long, default_value = "report_history.txt")]
+        history_path: String,
+    },
+    /// Encrypt every `day<n>.txt` input to `day<n>.txt.sealed` and remove the plaintext
+    Seal {
+        #[arg(long, default_value = "./inputs")]
+        inputs_dir: String,
+
+        /// Use the contents of this file as the key instead of AOC_INPUT_KEYFILE / AOC_INPUT_PASSPHRASE
+        #[arg(short, long)]
+        keyfile: Option<String>,
+    },
+    /// Decrypt every sealed input back to plaintext alongside the sealed copy
+    Unseal {
+        #[arg(long, default_value = "./inputs")]
+        inputs_dir: String,
+
+        /// Use the contents of this file as the key instead of AOC_INPUT_KEYFILE / AOC_INPUT_PASSPHRASE
+        #[arg(short, long)]
+        keyfile: Option<String>,
+    },
+    /// Run every registered variant of a day on the same input, checking they agree and comparing timings
+    Compare {
+        #[arg(short, long)]
+        day: u8,
+
+        #[arg(short, long)]
+        input_path: String,
+
+        /// Only compare the variants of this part
+        #[arg(short, long)]
+        part: Option<u8>,
+    },
+    /// Feed stdin to a day's parser, for driving the parsers from an external fuzzer
+    Fuzz {
+        #[arg(short, long)]
+        day: u8,
+    },
+    /// Write a random but puzzle-shaped input for a day, reproducible from the seed
+    Generate {
+        #[arg(short, long)]
+        day: u8,
+
+        #[arg(short, long, default_value_t = 0)]
+        seed: u64,
+
+        /// How big the input is: lines, games, cards, hands or nodes depending on the day
+        #[arg(long, default_value_t = generate::DEFAULT_SIZE)]
+        size: usize,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output_path: Option<String>,
+    },
+    /// Sum the calibration values of a day 1 document, reading number words from any vocabulary
+    Calibrate {
+        #[arg(short, long)]
+        input_path: String,
+
+        /// A built-in vocabulary (en, de, fr, es) or a file of `<word> <value>` lines
+        #[arg(short, long, default_value = "en")]
+        vocabulary: String,
+
+        /// Match number words regardless of case
+        #[arg(long)]
+        ignore_case: bool,
+
+        /// Show every match on each line, which ones were used and what the line contributed
+        #[arg(long)]
+        explain: bool,
+
+        /// How each line's value is read from the numbers on it
+        #[arg(short, long, value_enum, default_value_t = day1::Extraction::FirstLastDigit)]
+        extraction: day1::Extraction,
+
+        /// Decode the input on this many threads, defaulting to one per core
+        #[arg(short, long)]
+        threads: Option<usize>,
+    },
+    /// Sum the ids of the day 2 games that were possible with each of several bags
+    Bags {
+        #[arg(short, long)]
+        input_path: String,
+
+        /// A bag such as `red=12,green=13,blue=14`; may be given more than once
+        #[arg(short, long)]
+        bag: Vec<String>,
+
+        /// Also read bags from this file, one per line
+        #[arg(long)]
+        bags_path: Option<String>,
+    },
+    /// Estimate the most likely bag behind each day 2 game, and how likely each proposed bag is
+    Infer {
+        #[arg(short, long)]
+        input_path: String,
+
+        /// A proposed bag such as `red=12,green=13,blue=14`; may be given more than once
+        #[arg(short, long)]
+        bag: Vec<String>,
+
+        /// Also read proposed bags from this file, one per line
+        #[arg(long)]
+        bags_path: Option<String>,
+
+        /// The most cubes a bag may hold when searching for the most likely one
+        #[arg(long, default_value_t = 100)]
+        max_cubes: u32,
+    },
+    /// Start a local HTTP server answering `POST /2023/day/{n}/part/{p}` with the input as the body
+    Serve {
+        #[arg(short, long, default_value = "127.0.0.1:2023")]
+        address: String,
+    },
 }
 
 fn main() {
-    println!(".: 🎄 Advent of Code 2023 🎄 :.");
     let args = Args::parse();
+    // Generated inputs may go to stdout, so keep the banner out of them.
+    if !matches!(args.command, Some(Command::Generate { .. })) {
+        println!(".: 🎄 Advent of Code 2023 🎄 :.");
+    }
+    let mut cache = if args.no_cache { None } else { Some(AnswerCache::load(DEFAULT_CACHE_PATH)) };
+    let mut solvers = registry();
+    // Only the commands that run solvers load plugins, so the others never report a broken one.
+    if matches!(args.command, None | Some(Command::Report { .. } | Command::Compare { .. } | Command::Serve { .. })) {
+        solvers.extend(plugins::load_plugins(&args.plugins_dir));
+    }
+
+    match args.command {
+        Some(Command::Report { format, output_path, inputs_dir, answers_path, history_path }) => {
+            report::report(ReportOptions { inputs_dir, answers_path, history_path, format, output_path }, &solvers, &mut cache)
+        }
+        Some(Command::Seal { inputs_dir, keyfile }) => {
+            let sealed = Secret::resolve(keyfile).and_then(|s| seal::seal_dir(&inputs_dir, &s));
+            match sealed {
+                Ok(paths) => paths.iter().for_each(|p| println!("Sealed {}", p)),
+                Err(e) => println!("Failed to seal inputs: {}", e),
+            }
+        }
+        Some(Command::Unseal { inputs_dir, keyfile }) => {
+            let unsealed = Secret::resolve(keyfile).and_then(|s| seal::unseal_dir(&inputs_dir, &s));
+            match unsealed {
+                Ok(paths) => paths.iter().for_each(|p| println!("Unsealed {}", p)),
+                Err(e) => println!("Failed to unseal inputs: {}", e),
+            }
+        }
+        Some(Command::Compare { day, input_path, part }) => {
+            let i = load_input(input_path);
+            if !compare::compare(&solvers, day, part, &i, &mut cache) {
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Fuzz { day }) => {
+            let Some(target) = fuzz::target(day) else {
+                println!("No fuzz target for day {}", day);
+                return;
+            };
+            let mut data = vec![];
+            _ = std::io::stdin().read_to_end(&mut data);
+            target(&data);
+        }
+        Some(Command::Generate { day, seed, size, output_path }) => {
+            let Some(input) = generate::generate(day, seed, size) else {
+                eprintln!("No input generator for day {}", day);
+                std::process::exit(1);
+            };
+            match output_path {
+                Some(path) => std::fs::write(&path, input).unwrap_or_else(|e| panic!("Could not write {}: {}", path, e)),
+                None => println!("{}", input),
+            }
+        }
+        Some(Command::Calibrate { input_path, vocabulary, ignore_case, explain, extraction, threads }) => {
+            let vocabulary = match day1::Vocabulary::load(&vocabulary) {
+                Ok(vocabulary) => vocabulary,
+                Err(e) => {
+                    println!("Could not load vocabulary {}: {}", vocabulary, e);
+                    std::process::exit(1);
+                }
+            };
+            let i = load_input(input_path);
+            let patterns = day1::CalibrationPatterns::with_words(&vocabulary, ignore_case).with_extraction(extraction);
+            if explain {
+                let mut zero_lines = vec![];
+                for explanation in day1::explain(&i, &patterns) {
+                    print!("{}", explanation);
+                    if explanation.value == 0 {
+                        zero_lines.push(explanation.line_number);
+                    }
+                }
+                println!("{} line(s) contributed 0: {:?}", zero_lines.len(), zero_lines);
+            }
+            let threads = threads.unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+            let start = Instant::now();
+            let total = day1::calibrate_parallel(&i, &patterns, threads);
+            let elapsed = start.elapsed();
+            println!("Calibration value: {}", total);
+            println!(
+                "Decoded {:.2} MB on {} thread(s) in {:.2?} ({:.2} MB/s)",
+                i.len() as f64 / 1e6,
+                threads,
+                elapsed,
+                i.len() as f64 / 1e6 / elapsed.as_secs_f64().max(f64::EPSILON)
+            );
+        }
+        Some(Command::Bags { input_path, bag, bags_path }) => {
+            let day2::GameLog { games, bags, .. } = load_game_log(&input_path, &bag, bags_path.as_deref());
+            let width = bags.iter().map(|b| b.label.len()).max().unwrap_or(0).max(3);
+            println!("{:<width$}  {:>8}  {:>10}", "Bag", "Possible", "Sum of ids", width = width);
+            for bag in bags.iter() {
+                let (count, sum) = day2::possible_games(&games, bag);
+                println!("{:<width$}  {:>8}  {:>10}", bag.label, format!("{}/{}", count, games.len()), sum, width = width);
+            }
+        }
+        Some(Command::Infer { input_path, bag, bags_path, max_cubes }) => {
+            let day2::GameLog { colours, games, bags } = load_game_log(&input_path, &bag, bags_path.as_deref());
+
+            let mut totals = vec![0.0; bags.len()];
+            for game in games.iter() {
+                match day2::most_likely_bag(game, max_cubes) {
+                    Some(estimate) => {
+                        let limit = if estimate.at_limit { format!(", at the {} cube limit", max_cubes) } else { String::new() };
+                        println!("Game {}: most likely {} (likelihood {:.3e}{})", game.id, estimate.describe(&colours), estimate.log_likelihood.exp(), limit);
+                    }
+                    None => println!("Game {}: needs more than {} cubes", game.id, max_cubes),
+                }
+                for (bag, total) in bags.iter().zip(totals.iter_mut()) {
+                    let log_likelihood = day2::log_likelihood(game, bag);
+                    *total += log_likelihood;
+                    println!("  {}: {:.3e}", bag.label, log_likelihood.exp());
+                }
+            }
+
+            println!("Log-likelihood of every game:");
+            for (bag, total) in bags.iter().zip(totals) {
+                println!("  {}: {:.3}", bag.label, total);
+            }
+        }
+        Some(Command::Serve { address }) => serve::serve(&address, &solvers, &mut cache),
+        None => match args.query {
+            Some(query) => run_query(args.day.unwrap(), &args.input_path.unwrap(), &query),
+            None => run_day(&solvers, args.day.unwrap(), args.input_path.unwrap(), &mut cache),
+        },
+    }
+}
+
+/// Loads the games and bags for a day 2 query or subcommand, exiting when either can't be read.
+fn load_game_log(input_path: &str, bags: &[String], bags_path: Option<&str>) -> day2::GameLog {
+    day2::GameLog::load(input_path, bags, bags_path).unwrap_or_else(|e| {
+        eprintln!("Could not read {}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Answers `query` over the day's games, which only day 2 has.
+fn run_query(day: u8, input_path: &str, query: &str) {
+    if day != 2 {
+        eprintln!("--query only applies to day 2, not day {}", day);
+        std::process::exit(2);
+    }
+
+    let day2::GameLog { colours, games, .. } = load_game_log(input_path, &[], None);
+    match query::run(query, &games, &colours) {
+        Ok(answer) => println!("{}", answer),
+        Err(e) => {
+            eprintln!("Could not run query: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_day(solvers: &[Solver], day: u8, input_path: String, cache: &mut Option<AnswerCache>) {
+    let solvers = primary(solvers).into_iter().filter(|s| s.day == day).collect::<Vec<_>>();
+    if solvers.is_empty() {
+        println!("Implementation for provided day (day {day}) not found. ");
+        return;
+    }
 
-    match args.day {
-        1 => day1::day1(args.input_path),
-        2 => day2::day2(args.input_path),
-        3 => day3::day3(args.input_path),
-        4 => day4::day4(args.input_path),
-        5 => day5::day5(args.input_path),
-        6 => day6::day6(args.input_path),
-        7 => day7::day7(args.input_path),
-        8 => day8::day8(args.input_path),
-        n => { println!("Implementation for provided day (day {n}) not found. ")}
-    };
+    let i = load_input(input_path);
+    for solver in solvers {
+        let outcome = run_solver(cache, solver, &i);
+        let cached = if outcome.cached { " (cached)" } else { "" };
+        let name = if solver.name == BUILTIN { String::new() } else { format!(" ({})", solver.name) };
+        println!("Day {} Part {}{}: {} [{:.2?}{}]", solver.day, solver.part, name, outcome.answer, outcome.elapsed, cached);
+    }
 }
@@ -0,0 +1,187 @@
+//! Loads out-of-tree solvers from shared libraries.
+//!
+//! A plugin is any `cdylib` exporting these C-ABI symbols:
+//!
+//! ```c
+//! uint32_t aoc_plugin_abi_version(void);            // must return AOC_PLUGIN_ABI_VERSION
+//! void     aoc_plugin_register(AocRegistrar *reg);  // calls reg->register once per solver
+//! void     aoc_plugin_free_answer(char *answer);    // frees an answer returned by a solver
+//! ```
+//!
+//! Each registered solver is `char *solve(const uint8_t *input, size_t len)`, returning a
+//! NUL-terminated answer owned by the plugin, or NULL if it couldn't solve the input.
+//! See `examples/plugin_day6.rs` for a plugin written in Rust.
+
+use std::ffi::{c_char, c_void, CStr};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use libloading::{Library, Symbol};
+use crate::solutions::Solver;
+use crate::utilities::fingerprint;
+
+pub(crate) const AOC_PLUGIN_ABI_VERSION: u32 = 1;
+
+pub(crate) type PluginSolveFn = unsafe extern "C" fn(input: *const u8, len: usize) -> *mut c_char;
+type PluginFreeFn = unsafe extern "C" fn(answer: *mut c_char);
+type RegisterFn = unsafe extern "C" fn(ctx: *mut c_void, day: u8, part: u8, name: *const c_char, solve: PluginSolveFn);
+
+/// Handed to `aoc_plugin_register`, which calls `register(ctx, ...)` for each solver it provides.
+#[repr(C)]
+pub(crate) struct AocRegistrar {
+    ctx: *mut c_void,
+    register: RegisterFn,
+}
+
+struct Registration {
+    day: u8,
+    part: u8,
+    name: String,
+    solve: PluginSolveFn,
+}
+
+#[derive(Debug)]
+pub(crate) enum PluginError {
+    Load(libloading::Error),
+    AbiMismatch(u32),
+}
+
+impl Display for PluginError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::Load(e) => write!(f, "{}", e),
+            PluginError::AbiMismatch(v) => write!(f, "plugin ABI version {} is not supported (expected {})", v, AOC_PLUGIN_ABI_VERSION),
+        }
+    }
+}
+
+impl From<libloading::Error> for PluginError {
+    fn from(value: libloading::Error) -> Self {
+        PluginError::Load(value)
+    }
+}
+
+unsafe extern "C" fn register(ctx: *mut c_void, day: u8, part: u8, name: *const c_char, solve: PluginSolveFn) {
+    let registrations = &mut *(ctx as *mut Vec<Registration>);
+    let name = if name.is_null() { String::new() } else { CStr::from_ptr(name).to_string_lossy().to_string() };
+    registrations.push(Registration { day, part, name, solve });
+}
+
+/// Loads every shared library in `dir`, skipping (and reporting on stderr) any that fail to load.
+/// A missing directory simply means there are no plugins.
+pub(crate) fn load_plugins(dir: &str) -> Vec<Solver> {
+    let Ok(entries) = fs::read_dir(dir) else { return vec![] };
+
+    let mut paths = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    paths.iter().flat_map(|path| match load_plugin(path) {
+        Ok(solvers) => solvers,
+        Err(e) => {
+            eprintln!("Skipping plugin {}: {}", path.display(), e);
+            vec![]
+        }
+    }).collect()
+}
+
+pub(crate) fn load_plugin(path: &Path) -> Result<Vec<Solver>, PluginError> {
+    let plugin_name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let version = fingerprint(&fs::read(path).unwrap_or_default());
+
+    unsafe {
+        let library = Arc::new(Library::new(path)?);
+
+        let abi_version: Symbol<unsafe extern "C" fn() -> u32> = library.get(b"aoc_plugin_abi_version")?;
+        let abi_version = abi_version();
+        if abi_version != AOC_PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch(abi_version));
+        }
+
+        let plugin_register: Symbol<unsafe extern "C" fn(*mut AocRegistrar)> = library.get(b"aoc_plugin_register")?;
+        let free_answer: PluginFreeFn = *library.get::<PluginFreeFn>(b"aoc_plugin_free_answer")?;
+
+        let registrations = collect_registrations(|registrar| plugin_register(registrar));
+
+        Ok(registrations.into_iter().map(|r| {
+            let library = library.clone();
+            let name = if r.name.is_empty() { plugin_name.clone() } else { format!("{}::{}", plugin_name, r.name) };
+            Solver {
+                day: r.day,
+                part: r.part,
+                name,
+                version,
+                solve: Box::new(move |i| {
+                    let _library = &library;
+                    call_solver(r.solve, free_answer, i)
+                }),
+            }
+        }).collect())
+    }
+}
+
+fn collect_registrations(plugin_register: impl FnOnce(*mut AocRegistrar)) -> Vec<Registration> {
+    let mut registrations: Vec<Registration> = vec![];
+    let mut registrar = AocRegistrar {
+        ctx: &mut registrations as *mut Vec<Registration> as *mut c_void,
+        register,
+    };
+    plugin_register(&mut registrar);
+
+    registrations
+}
+
+fn call_solver(solve: PluginSolveFn, free_answer: PluginFreeFn, i: &str) -> String {
+    unsafe {
+        let answer = solve(i.as_ptr(), i.len());
+        if answer.is_null() {
+            panic!("plugin solver could not solve the given input");
+        }
+
+        let result = CStr::from_ptr(answer).to_string_lossy().to_string();
+        free_answer(answer);
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::CString;
+
+    unsafe extern "C" fn count_lines(input: *const u8, len: usize) -> *mut c_char {
+        let input = std::str::from_utf8(std::slice::from_raw_parts(input, len)).unwrap();
+        CString::new(input.lines().count().to_string()).unwrap().into_raw()
+    }
+
+    unsafe extern "C" fn free_answer(answer: *mut c_char) {
+        drop(CString::from_raw(answer));
+    }
+
+    #[test]
+    fn should_collect_solvers_registered_through_registrar() {
+        let registrations = collect_registrations(|registrar| unsafe {
+            let registrar = &*registrar;
+            (registrar.register)(registrar.ctx, 6, 1, c"lines".as_ptr(), count_lines);
+            (registrar.register)(registrar.ctx, 6, 2, std::ptr::null(), count_lines);
+        });
+
+        assert_eq!(registrations.len(), 2);
+        assert_eq!((registrations[0].day, registrations[0].part, registrations[0].name.as_str()), (6, 1, "lines"));
+        assert_eq!((registrations[1].day, registrations[1].part, registrations[1].name.as_str()), (6, 2, ""));
+    }
+
+    #[test]
+    fn should_call_plugin_solver_across_c_abi() {
+        assert_eq!(call_solver(count_lines, free_answer, "Time: 7\nDistance: 9"), "2");
+    }
+
+    #[test]
+    fn should_skip_missing_plugins_dir() {
+        assert!(load_plugins("./does-not-exist").is_empty());
+    }
+}
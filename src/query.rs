@@ -0,0 +1,464 @@
+//! A small query language over day 2 game logs.
+//!
+//! A query is either a condition, which lists the ids of the games it holds for, or an aggregate
+//! over the games, optionally narrowed with `where <condition>`:
+//!
+//! ```text
+//! max(red) > 12 and count(reveals) >= 3
+//! sum(min_bag_power) where id < 50
+//! avg(count(reveals)) where not (blue > 10)
+//! AVG(red) * -1.5 WHERE id >= 10
+//! ```
+//!
+//! Numbers may have a fractional part and be negated, and keywords and names are read in any case.
+//!
+//! Names mean different things depending on what they're evaluated against:
+//!
+//! - a reveal: a colour is how many of it were drawn, `cubes` is how many were drawn in all and
+//!   `reveals` is 1;
+//! - a game: a colour is the most of it seen in one reveal (0 if never), `id` is what it says,
+//!   `min_bag_power` is the puzzle's power of the red, green and blue cubes needed (so 0 if any of
+//!   them is never seen) and `games` is 1.
+//!
+//! Any other name is an error, including a colour no game ever draws.
+//!
+//! `sum`, `min`, `max`, `avg` and `count` step down a level: over the games from the top of a
+//! query, over a game's reveals within a game. `count` counts the items its argument holds for
+//! (or is non-zero for), so `count(reveals)` counts them all and `count(red > 3)` only some.
+
+use std::fmt::{Display, Formatter};
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case, take_while1};
+use nom::character::complete::{char, digit1, multispace0};
+use nom::combinator::{cut, eof, map, map_res, not, opt, peek, recognize, verify};
+use nom::error::{context, VerboseError, VerboseErrorKind};
+use nom::IResult;
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, terminated, tuple};
+use crate::solutions::day2::{drawn, get_power_of_minimum_required, reveal_size, BallData, Colour, Colours, GameData};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Name(String),
+    Call(String, Box<Expr>),
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Box<Expr>, Op, Box<Expr>),
+}
+
+impl Expr {
+    /// Whether the expression is a condition rather than a number, judged by its outermost operator.
+    fn is_condition(&self) -> bool {
+        match self {
+            Expr::Not(_) => true,
+            Expr::Binary(_, op, _) => !matches!(op, Op::Add | Op::Subtract | Op::Multiply | Op::Divide),
+            _ => false,
+        }
+    }
+}
+
+struct Query {
+    select: Expr,
+    filter: Option<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Number(f64),
+    Bool(bool),
+}
+
+/// What an expression is evaluated against.
+#[derive(Clone, Copy)]
+enum Scope<'a> {
+    Games(&'a [&'a GameData]),
+    Game(&'a GameData),
+    Reveal(&'a [BallData]),
+}
+
+impl Scope<'_> {
+    fn describe(&self) -> &'static str {
+        match self {
+            Scope::Games(_) => "all the games",
+            Scope::Game(_) => "a game",
+            Scope::Reveal(_) => "a reveal",
+        }
+    }
+}
+
+/// The answer to a query: the games a condition held for, or the value of an aggregate.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Answer {
+    Games(Vec<u32>),
+    Value(f64),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Games(ids) => write!(f, "{} game(s): {}", ids.len(), ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ")),
+            Answer::Value(value) if value.fract() == 0.0 && value.abs() < 1e15 => write!(f, "{}", *value as i64),
+            Answer::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum QueryError {
+    Parse { column: usize, expected: &'static str },
+    Eval(String),
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::Parse { column, expected } => write!(f, "column {}: expected {}", column, expected),
+            QueryError::Eval(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Parses and runs `query` against `games`, whose colours were interned into `colours`.
+pub(crate) fn run(query: &str, games: &[GameData], colours: &Colours) -> Result<Answer, QueryError> {
+    let Query { select, filter } = parse(query)?;
+    let evaluator = Evaluator { colours };
+
+    let mut selected = vec![];
+    for game in games.iter() {
+        let keep = match &filter {
+            Some(filter) => evaluator.condition(filter, Scope::Game(game))?,
+            None => true,
+        };
+        if keep {
+            selected.push(game);
+        }
+    }
+
+    if select.is_condition() {
+        let mut ids = vec![];
+        for game in selected {
+            if evaluator.condition(&select, Scope::Game(game))? {
+                ids.push(game.id);
+            }
+        }
+        Ok(Answer::Games(ids))
+    } else {
+        evaluator.number(&select, Scope::Games(&selected)).map(Answer::Value)
+    }
+}
+
+struct Evaluator<'a> {
+    colours: &'a Colours,
+}
+
+impl Evaluator<'_> {
+    fn number(&self, expr: &Expr, scope: Scope) -> Result<f64, QueryError> {
+        match self.evaluate(expr, scope)? {
+            Value::Number(n) => Ok(n),
+            Value::Bool(_) => Err(QueryError::Eval("expected a number but found a condition".to_owned())),
+        }
+    }
+
+    fn condition(&self, expr: &Expr, scope: Scope) -> Result<bool, QueryError> {
+        match self.evaluate(expr, scope)? {
+            Value::Bool(b) => Ok(b),
+            Value::Number(_) => Err(QueryError::Eval("expected a condition but found a number".to_owned())),
+        }
+    }
+
+    fn evaluate(&self, expr: &Expr, scope: Scope) -> Result<Value, QueryError> {
+        match expr {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::Name(name) => self.name(name, scope),
+            Expr::Call(function, argument) => self.call(function, argument, scope),
+            Expr::Negate(inner) => Ok(Value::Number(-self.number(inner, scope)?)),
+            Expr::Not(inner) => Ok(Value::Bool(!self.condition(inner, scope)?)),
+            Expr::Binary(left, Op::And, right) => Ok(Value::Bool(self.condition(left, scope)? && self.condition(right, scope)?)),
+            Expr::Binary(left, Op::Or, right) => Ok(Value::Bool(self.condition(left, scope)? || self.condition(right, scope)?)),
+            Expr::Binary(left, op, right) => {
+                let (l, r) = (self.number(left, scope)?, self.number(right, scope)?);
+                Ok(match op {
+                    Op::Add => Value::Number(l + r),
+                    Op::Subtract => Value::Number(l - r),
+                    Op::Multiply => Value::Number(l * r),
+                    Op::Divide if r == 0.0 => return Err(QueryError::Eval("division by zero".to_owned())),
+                    Op::Divide => Value::Number(l / r),
+                    Op::Equal => Value::Bool(l == r),
+                    Op::NotEqual => Value::Bool(l != r),
+                    Op::Less => Value::Bool(l < r),
+                    Op::LessOrEqual => Value::Bool(l <= r),
+                    Op::Greater => Value::Bool(l > r),
+                    Op::GreaterOrEqual => Value::Bool(l >= r),
+                    Op::And | Op::Or => unreachable!(),
+                })
+            }
+        }
+    }
+
+    fn name(&self, name: &str, scope: Scope) -> Result<Value, QueryError> {
+        let value = match (name, scope) {
+            ("games", Scope::Game(_)) | ("reveals", Scope::Reveal(_)) => 1,
+            ("id", Scope::Game(game)) => game.id,
+            // The puzzle's power is over red, green and blue, so a game missing one of them has none.
+            ("min_bag_power", Scope::Game(game)) => match ["red", "green", "blue"].map(|c| self.colours.id(c)) {
+                [Some(red), Some(green), Some(blue)] => get_power_of_minimum_required(game, &[red, green, blue]),
+                _ => 0,
+            },
+            ("cubes", Scope::Reveal(reveal)) => reveal_size(reveal),
+            ("games" | "reveals" | "id" | "min_bag_power" | "cubes", _) | (_, Scope::Games(_)) => {
+                return Err(QueryError::Eval(format!("`{}` doesn't mean anything for {}", name, scope.describe())));
+            }
+            (colour, Scope::Game(game)) => {
+                let colour = self.colour(colour)?;
+                game.get_max_colours_seen().get(&colour).copied().unwrap_or(0)
+            }
+            (colour, Scope::Reveal(reveal)) => drawn(reveal, self.colour(colour)?),
+        };
+
+        Ok(Value::Number(value as f64))
+    }
+
+    /// A colour drawn somewhere in the input, so a misspelt name fails rather than quietly being 0.
+    fn colour(&self, name: &str) -> Result<Colour, QueryError> {
+        self.colours.id(name).ok_or_else(|| QueryError::Eval(format!("unknown name `{}`: not a colour in any game", name)))
+    }
+
+    fn call(&self, function: &str, argument: &Expr, scope: Scope) -> Result<Value, QueryError> {
+        let items = match scope {
+            Scope::Games(games) => games.iter().map(|g| Scope::Game(g)).collect::<Vec<_>>(),
+            Scope::Game(game) => game.revealed.iter().map(|r| Scope::Reveal(r)).collect(),
+            Scope::Reveal(_) => return Err(QueryError::Eval(format!("`{}` has nothing to range over within a reveal", function))),
+        };
+
+        if function == "count" {
+            let mut count = 0;
+            for item in items {
+                let holds = match self.evaluate(argument, item)? {
+                    Value::Bool(b) => b,
+                    Value::Number(n) => n != 0.0,
+                };
+                count += holds as usize;
+            }
+            return Ok(Value::Number(count as f64));
+        }
+
+        let values = items.into_iter().map(|item| self.number(argument, item)).collect::<Result<Vec<f64>, QueryError>>()?;
+        let value = match function {
+            "sum" => values.iter().sum(),
+            "min" => values.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            "max" => values.iter().copied().reduce(f64::max).unwrap_or(0.0),
+            "avg" if values.is_empty() => 0.0,
+            "avg" => values.iter().sum::<f64>() / values.len() as f64,
+            other => return Err(QueryError::Eval(format!("unknown function `{}`", other))),
+        };
+
+        Ok(Value::Number(value))
+    }
+}
+
+type ParseResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+const KEYWORDS: [&str; 4] = ["and", "or", "not", "where"];
+
+fn parse(query: &str) -> Result<Query, QueryError> {
+    let parsed = tuple((
+        parse_expr,
+        opt(preceded(keyword("where"), cut(parse_expr))),
+        context("an operator, `where` or the end of the query", preceded(multispace0, eof)),
+    ))(query);
+
+    match parsed {
+        Ok((_, (select, filter, _))) => Ok(Query { select, filter }),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+            let (rest, expected) = e.errors.iter()
+                .find_map(|(rest, kind)| match kind {
+                    VerboseErrorKind::Context(expected) => Some((*rest, *expected)),
+                    _ => None,
+                })
+                .unwrap_or(("", "a query"));
+            Err(QueryError::Parse { column: query.len() - rest.len() + 1, expected })
+        }
+        Err(nom::Err::Incomplete(_)) => Err(QueryError::Parse { column: query.len() + 1, expected: "a query" }),
+    }
+}
+
+/// A whole word in any case, so `or` matches `OR` but not the start of `orange`.
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    preceded(multispace0, terminated(tag_no_case(word), not(peek(take_while1(is_name_char)))))
+}
+
+fn symbol<'a>(symbol: &'static str) -> impl FnMut(&'a str) -> ParseResult<'a, &'a str> {
+    preceded(multispace0, tag(symbol))
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Operands joined left to right by any of `operators`.
+fn chain<'a>(
+    operand: fn(&'a str) -> ParseResult<'a, Expr>,
+    operators: fn(&'a str) -> ParseResult<'a, Op>,
+) -> impl FnMut(&'a str) -> ParseResult<'a, Expr> {
+    move |i| {
+        let (i, first) = operand(i)?;
+        let (i, rest) = many0(pair(operators, cut(operand)))(i)?;
+        Ok((i, rest.into_iter().fold(first, |left, (op, right)| Expr::Binary(Box::new(left), op, Box::new(right)))))
+    }
+}
+
+fn parse_expr(i: &str) -> ParseResult<'_, Expr> {
+    chain(parse_and, |i| map(keyword("or"), |_| Op::Or)(i))(i)
+}
+
+fn parse_and(i: &str) -> ParseResult<'_, Expr> {
+    chain(parse_not, |i| map(keyword("and"), |_| Op::And)(i))(i)
+}
+
+fn parse_not(i: &str) -> ParseResult<'_, Expr> {
+    alt((
+        map(preceded(keyword("not"), cut(parse_not)), |e| Expr::Not(Box::new(e))),
+        parse_comparison,
+    ))(i)
+}
+
+fn parse_comparison(i: &str) -> ParseResult<'_, Expr> {
+    let (i, left) = parse_sum(i)?;
+    let (i, right) = opt(pair(parse_comparison_op, cut(parse_sum)))(i)?;
+    Ok((i, match right {
+        Some((op, right)) => Expr::Binary(Box::new(left), op, Box::new(right)),
+        None => left,
+    }))
+}
+
+fn parse_comparison_op(i: &str) -> ParseResult<'_, Op> {
+    alt((
+        map(symbol(">="), |_| Op::GreaterOrEqual),
+        map(symbol("<="), |_| Op::LessOrEqual),
+        map(symbol("!="), |_| Op::NotEqual),
+        map(symbol("=="), |_| Op::Equal),
+        map(symbol("="), |_| Op::Equal),
+        map(symbol(">"), |_| Op::Greater),
+        map(symbol("<"), |_| Op::Less),
+    ))(i)
+}
+
+fn parse_sum(i: &str) -> ParseResult<'_, Expr> {
+    chain(parse_term, |i| alt((map(symbol("+"), |_| Op::Add), map(symbol("-"), |_| Op::Subtract)))(i))(i)
+}
+
+fn parse_term(i: &str) -> ParseResult<'_, Expr> {
+    chain(parse_atom, |i| alt((map(symbol("*"), |_| Op::Multiply), map(symbol("/"), |_| Op::Divide)))(i))(i)
+}
+
+fn parse_atom(i: &str) -> ParseResult<'_, Expr> {
+    context("a number, a name, a function call or `(`", preceded(multispace0, alt((
+        map(map_res(recognize(pair(digit1, opt(pair(char('.'), digit1)))), str::parse::<f64>), Expr::Number),
+        map(preceded(char('-'), cut(parse_atom)), |e| Expr::Negate(Box::new(e))),
+        delimited(char('('), cut(parse_expr), context("`)`", cut(symbol(")")))),
+        parse_name_or_call,
+    ))))(i)
+}
+
+fn parse_name_or_call(i: &str) -> ParseResult<'_, Expr> {
+    let (i, name) = verify(take_while1(is_name_char), |name: &str| !KEYWORDS.contains(&name.to_lowercase().as_str()))(i)?;
+    let (i, argument) = opt(delimited(symbol("("), cut(parse_expr), context("`)`", cut(symbol(")")))))(i)?;
+    Ok((i, match argument {
+        Some(argument) => Expr::Call(name.to_lowercase(), Box::new(argument)),
+        None => Expr::Name(name.to_lowercase()),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::solutions::day2::{parse_games, part2};
+
+    const EXAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    fn query(query: &str) -> Result<Answer, QueryError> {
+        let mut colours = Colours::new();
+        let games = parse_games(EXAMPLE, &mut colours).unwrap();
+        run(query, &games, &colours)
+    }
+
+    #[test]
+    fn should_aggregate_over_games() {
+        assert_eq!(query("sum(min_bag_power)"), Ok(Answer::Value(2286.0)));
+        assert_eq!(query("sum(id) where red <= 12 and green <= 13 and blue <= 14"), Ok(Answer::Value(8.0)));
+        assert_eq!(query("count(games) where id < 3"), Ok(Answer::Value(2.0)));
+        assert_eq!(query("avg(count(reveals))"), Ok(Answer::Value(2.8)));
+        assert_eq!(query("max(sum(cubes)) - min(id * 2)"), Ok(Answer::Value(60.0)));
+    }
+
+    #[test]
+    fn min_bag_power_should_match_part2() {
+        let mut colours = Colours::new();
+        let input = "Game 1: 2 red, 3 green, 4 blue, 5 teal\nGame 2: 2 red, 3 teal";
+        let games = parse_games(input, &mut colours).unwrap();
+        assert_eq!(run("sum(min_bag_power) where id = 1", &games, &colours), Ok(Answer::Value(24.0)));
+        assert_eq!(run("sum(min_bag_power) where id = 2", &games, &colours), Ok(Answer::Value(0.0)));
+        assert_eq!(run("sum(min_bag_power)", &games, &colours), Ok(Answer::Value(part2(input) as f64)));
+    }
+
+    #[test]
+    fn should_filter_games() {
+        assert_eq!(query("max(red) > 12 and count(reveals) >= 3"), Ok(Answer::Games(vec![3, 4])));
+        assert_eq!(query("count(blue > 5) >= 2"), Ok(Answer::Games(vec![4])));
+        assert_eq!(query("not (red > 12 or green >= 0) or id = 5"), Ok(Answer::Games(vec![5])));
+        assert_eq!(query("RED > 12 where id != 3"), Ok(Answer::Games(vec![4])));
+    }
+
+    #[test]
+    fn should_read_decimals_and_negatives() {
+        assert_eq!(query("avg(count(reveals)) - 2.8"), Ok(Answer::Value(0.0)));
+        assert_eq!(query("id * -1.5 > -3"), Ok(Answer::Games(vec![1])));
+        assert_eq!(query("-sum(id) + --1"), Ok(Answer::Value(-14.0)));
+        assert_eq!(query("id > 1."), Err(QueryError::Parse { column: 7, expected: "an operator, `where` or the end of the query" }));
+    }
+
+    #[test]
+    fn should_read_keywords_in_any_case() {
+        assert_eq!(query("NOT (red > 12 Or blue > 10) AND id != 2"), Ok(Answer::Games(vec![1, 5])));
+        assert_eq!(query("count(games) WHERE id < 3"), Ok(Answer::Value(2.0)));
+        assert!(matches!(query("sum(And)"), Err(QueryError::Parse { .. })));
+    }
+
+    #[test]
+    fn should_report_parse_errors_with_position() {
+        assert_eq!(query("sum(id"), Err(QueryError::Parse { column: 7, expected: "`)`" }));
+        assert_eq!(query("red > "), Err(QueryError::Parse { column: 6, expected: "a number, a name, a function call or `(`" }));
+        assert_eq!(query("red 3"), Err(QueryError::Parse { column: 4, expected: "an operator, `where` or the end of the query" }));
+    }
+
+    #[test]
+    fn should_reject_names_out_of_place() {
+        assert!(matches!(query("sum(red) + id"), Err(QueryError::Eval(_))));
+        assert!(matches!(query("id and red"), Err(QueryError::Eval(_))));
+        assert!(matches!(query("median(id)"), Err(QueryError::Eval(_))));
+        assert_eq!(query("count(purple > 0) > 0").unwrap_err().to_string(), "unknown name `purple`: not a colour in any game");
+        assert!(matches!(query("max(red) > 12 and blu > 10"), Err(QueryError::Eval(_))));
+        assert_eq!(query("sum(id) / 0").unwrap_err().to_string(), "division by zero");
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use clap::ValueEnum;
+use crate::cache::{run_solver, AnswerCache};
+use crate::solutions::{primary, Solver};
+use crate::utilities::input::load_input;
+use crate::utilities::input_exists;
+
+const SPARK_TICKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARK_LENGTH: usize = 10;
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub(crate) enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+pub(crate) struct ReportOptions {
+    pub inputs_dir: String,
+    pub answers_path: String,
+    pub history_path: String,
+    pub format: ReportFormat,
+    pub output_path: String,
+}
+
+struct ReportRow {
+    day: u8,
+    part: u8,
+    answer: String,
+    elapsed: Duration,
+    cached: bool,
+    input_size: usize,
+    expected: Option<String>,
+    history: Vec<u128>,
+}
+
+/// How a part's answer compares with the one stored in the answers file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Fail,
+    NoStoredAnswer,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Pass => write!(f, "✅ pass"),
+            Status::Fail => write!(f, "❌ fail"),
+            Status::NoStoredAnswer => write!(f, "➖ no stored answer"),
+        }
+    }
+}
+
+impl ReportRow {
+    fn status(&self) -> Status {
+        match &self.expected {
+            None => Status::NoStoredAnswer,
+            Some(e) if *e == self.answer => Status::Pass,
+            Some(_) => Status::Fail,
+        }
+    }
+}
+
+/// Runs the primary solver for every day/part against `<inputs_dir>/day<n>.txt` and writes the season report.
+pub(crate) fn report(options: ReportOptions, solvers: &[Solver], cache: &mut Option<AnswerCache>) {
+    let answers = load_answers(&options.answers_path);
+    let mut history = load_history(&options.history_path);
+    let run_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+    let mut rows = vec![];
+    for solver in primary(solvers) {
+        let input_path = format!("{}/day{}.txt", options.inputs_dir, solver.day);
+        if !input_exists(&input_path) {
+            continue;
+        }
+
+        let i = load_input(input_path);
+        let outcome = run_solver(cache, solver, &i);
+
+        let timings = history.entry((solver.day, solver.part)).or_default();
+        if !outcome.cached {
+            timings.push(outcome.elapsed.as_micros());
+        }
+
+        rows.push(ReportRow {
+            day: solver.day,
+            part: solver.part,
+            answer: outcome.answer,
+            elapsed: outcome.elapsed,
+            cached: outcome.cached,
+            input_size: i.len(),
+            expected: answers.get(&(solver.day, solver.part)).cloned(),
+            history: timings.clone(),
+        });
+    }
+
+    append_history(&options.history_path, run_at, &rows);
+
+    let page = match options.format {
+        ReportFormat::Markdown => render_markdown(&rows),
+        ReportFormat::Html => render_html(&rows),
+    };
+    fs::write(&options.output_path, page).unwrap();
+
+    let failed = rows.iter().filter(|r| r.status() == Status::Fail).count();
+    println!("Report written to {} ({} parts, {} failing)", options.output_path, rows.len(), failed);
+}
+
+/// Stored answers are kept one per line as `<day> <part> <answer>`.
+fn load_answers(path: &str) -> HashMap<(u8, u8), String> {
+    let Ok(contents) = fs::read_to_string(path) else { return HashMap::new() };
+
+    contents.lines().filter_map(|l| {
+        let mut parts = l.split_whitespace();
+        let day = parts.next()?.parse().ok()?;
+        let part = parts.next()?.parse().ok()?;
+        let answer = parts.next()?.to_owned();
+        Some(((day, part), answer))
+    }).collect()
+}
+
+/// Timing history is appended one line per part per run as `<unix time> <day> <part> <micros>`.
+fn load_history(path: &str) -> HashMap<(u8, u8), Vec<u128>> {
+    let Ok(contents) = fs::read_to_string(path) else { return HashMap::new() };
+
+    contents.lines().fold(HashMap::new(), |mut acc, l| {
+        let fields = l.split_whitespace().collect::<Vec<&str>>();
+        if let [_, day, part, micros] = fields[..] {
+            if let (Ok(day), Ok(part), Ok(micros)) = (day.parse(), part.parse(), micros.parse()) {
+                acc.entry((day, part)).or_insert_with(Vec::new).push(micros);
+            }
+        }
+        acc
+    })
+}
+
+fn append_history(path: &str, run_at: u64, rows: &[ReportRow]) {
+    let mut file = OpenOptions::new().create(true).append(true).open(path).unwrap();
+    for r in rows.iter().filter(|r| !r.cached) {
+        writeln!(file, "{} {} {} {}", run_at, r.day, r.part, r.elapsed.as_micros()).unwrap();
+    }
+}
+
+fn timing(row: &ReportRow) -> String {
+    if row.cached { format!("{:.2?} (cached)", row.elapsed) } else { format!("{:.2?}", row.elapsed) }
+}
+
+fn sparkline(timings: &[u128]) -> String {
+    let recent = &timings[timings.len().saturating_sub(SPARK_LENGTH)..];
+    let min = recent.iter().min().copied().unwrap_or(0);
+    let max = recent.iter().max().copied().unwrap_or(0);
+    let spread = (max - min).max(1);
+
+    recent.iter()
+        .map(|t| SPARK_TICKS[((t - min) * (SPARK_TICKS.len() as u128 - 1) / spread) as usize])
+        .collect()
+}
+
+fn render_markdown(rows: &[ReportRow]) -> String {
+    let mut page = String::from("# Advent of Code 2023\n\n");
+    page.push_str("| Day | Part | Answer | Time | Input size | Status | History |\n");
+    page.push_str("|----:|-----:|-------:|-----:|-----------:|--------|---------|\n");
+    for r in rows {
+        page.push_str(&format!(
+            "| {} | {} | `{}` | {} | {} B | {} | {} |\n",
+            r.day, r.part, r.answer, timing(r), r.input_size, r.status(), sparkline(&r.history)
+        ));
+    }
+
+    page
+}
+
+fn render_html(rows: &[ReportRow]) -> String {
+    let mut page = String::from("<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Advent of Code 2023</title></head>\n<body>\n<h1>Advent of Code 2023</h1>\n<table>\n");
+    page.push_str("<tr><th>Day</th><th>Part</th><th>Answer</th><th>Time</th><th>Input size</th><th>Status</th><th>History</th></tr>\n");
+    for r in rows {
+        page.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td><code>{}</code></td><td>{}</td><td>{} B</td><td>{}</td><td>{}</td></tr>\n",
+            r.day, r.part, escape_html(&r.answer), timing(r), r.input_size, r.status(), sparkline(&r.history)
+        ));
+    }
+    page.push_str("</table>\n</body>\n</html>\n");
+
+    page
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sparkline_should_scale_between_min_and_max() {
+        assert_eq!(sparkline(&[1, 8, 4]), "▁█▄");
+        assert_eq!(sparkline(&[5, 5, 5]), "▁▁▁");
+    }
+
+    #[test]
+    fn sparkline_should_only_show_most_recent_runs() {
+        let timings = (0..20).collect::<Vec<u128>>();
+        assert_eq!(sparkline(&timings).chars().count(), SPARK_LENGTH);
+    }
+
+    #[test]
+    fn status_should_compare_with_stored_answer() {
+        let row = |expected: Option<&str>| ReportRow {
+            day: 1,
+            part: 1,
+            answer: "42".to_owned(),
+            elapsed: Duration::ZERO,
+            cached: false,
+            input_size: 0,
+            expected: expected.map(str::to_owned),
+            history: vec![],
+        };
+        assert_eq!(row(Some("42")).status(), Status::Pass);
+        assert_eq!(row(Some("41")).status(), Status::Fail);
+        assert_eq!(row(None).status(), Status::NoStoredAnswer);
+        assert_eq!(row(Some("41")).status().to_string(), "❌ fail");
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use argon2::Argon2;
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use chacha20poly1305::aead::{Aead, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+
+/// Appended to an input's path to find its encrypted counterpart, e.g. `inputs/day1.txt.sealed`.
+pub(crate) const SEALED_EXTENSION: &str = ".sealed";
+
+pub(crate) const PASSPHRASE_ENV: &str = "AOC_INPUT_PASSPHRASE";
+pub(crate) const KEYFILE_ENV: &str = "AOC_INPUT_KEYFILE";
+
+const MAGIC: &[u8; 8] = b"AOCSEAL1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + NONCE_LEN;
+
+#[derive(Debug)]
+pub(crate) enum SealError {
+    MissingKey,
+    NotSealed,
+    KeyDerivation,
+    Encrypt,
+    Decrypt,
+    RoundTrip(String),
+    Io(std::io::Error),
+}
+
+impl Display for SealError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SealError::MissingKey => write!(f, "no key provided, set {} or {}", PASSPHRASE_ENV, KEYFILE_ENV),
+            SealError::NotSealed => write!(f, "file is not a sealed input"),
+            SealError::KeyDerivation => write!(f, "could not derive a key from the secret"),
+            SealError::Encrypt => write!(f, "could not encrypt input"),
+            SealError::Decrypt => write!(f, "could not decrypt input, wrong passphrase or corrupted file"),
+            SealError::RoundTrip(path) => write!(f, "sealed copy of {} does not decrypt to the original, plaintext kept", path),
+            SealError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for SealError {
+    fn from(value: std::io::Error) -> Self {
+        SealError::Io(value)
+    }
+}
+
+/// The secret used to derive the per-file encryption key - either a passphrase or the raw contents of a keyfile.
+pub(crate) struct Secret(Vec<u8>);
+
+impl Secret {
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        Self(passphrase.as_bytes().to_vec())
+    }
+
+    pub fn from_keyfile(path: &str) -> Result<Self, SealError> {
+        Ok(Self(fs::read(path)?))
+    }
+
+    /// Prefers an explicitly given keyfile, then `AOC_INPUT_KEYFILE`, then `AOC_INPUT_PASSPHRASE`.
+    pub fn resolve(keyfile: Option<String>) -> Result<Self, SealError> {
+        if let Some(path) = keyfile.or_else(|| std::env::var(KEYFILE_ENV).ok()) {
+            return Self::from_keyfile(&path);
+        }
+
+        match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) => Ok(Self::from_passphrase(&passphrase)),
+            Err(_) => Err(SealError::MissingKey),
+        }
+    }
+
+    fn derive_key(&self, salt: &[u8]) -> Result<Key, SealError> {
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(&self.0, salt, &mut key)
+            .map_err(|_| SealError::KeyDerivation)?;
+
+        Ok(key)
+    }
+}
+
+pub(crate) fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Sealed files are laid out as `MAGIC | salt | nonce | ciphertext`.
+pub(crate) fn seal_bytes(plaintext: &[u8], secret: &Secret) -> Result<Vec<u8>, SealError> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let cipher = ChaCha20Poly1305::new(&secret.derive_key(&salt)?);
+    let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), plaintext).map_err(|_| SealError::Encrypt)?;
+
+    let mut sealed = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+
+    Ok(sealed)
+}
+
+pub(crate) fn unseal_bytes(sealed: &[u8], secret: &Secret) -> Result<Vec<u8>, SealError> {
+    if !is_sealed(sealed) || sealed.len() < HEADER_LEN {
+        return Err(SealError::NotSealed);
+    }
+
+    let salt = &sealed[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let nonce = &sealed[MAGIC.len() + SALT_LEN..HEADER_LEN];
+    let cipher = ChaCha20Poly1305::new(&secret.derive_key(salt)?);
+
+    cipher.decrypt(Nonce::from_slice(nonce), &sealed[HEADER_LEN..]).map_err(|_| SealError::Decrypt)
+}
+
+/// Encrypts every plaintext `.txt` input in `dir` to `<name>.txt.sealed` and removes the plaintext once the
+/// sealed copy on disk has been read back and decrypted to the same bytes.
+pub(crate) fn seal_dir(dir: &str, secret: &Secret) -> Result<Vec<String>, SealError> {
+    let mut sealed = vec![];
+    for path in input_files(dir, ".txt")? {
+        let plaintext = fs::read(&path)?;
+        let sealed_path = format!("{}{}", path, SEALED_EXTENSION);
+        fs::write(&sealed_path, seal_bytes(&plaintext, secret)?)?;
+        if unseal_bytes(&fs::read(&sealed_path)?, secret)? != plaintext {
+            return Err(SealError::RoundTrip(path));
+        }
+        fs::remove_file(&path)?;
+        sealed.push(path);
+    }
+
+    Ok(sealed)
+}
+
+/// Decrypts every `.txt.sealed` input in `dir` back to its plaintext `.txt`, leaving the sealed copy in place.
+pub(crate) fn unseal_dir(dir: &str, secret: &Secret) -> Result<Vec<String>, SealError> {
+    let mut unsealed = vec![];
+    for path in input_files(dir, SEALED_EXTENSION)? {
+        let Some(plain_path) = path.strip_suffix(SEALED_EXTENSION) else { continue };
+        let plaintext = unseal_bytes(&fs::read(&path)?, secret)?;
+        fs::write(plain_path, plaintext)?;
+        unsealed.push(plain_path.to_owned());
+    }
+
+    Ok(unsealed)
+}
+
+/// Puzzle inputs in `dir` ending in `extension`. Examples like `day7_test.txt` are public and stay as they are.
+fn input_files(dir: &str, extension: &str) -> Result<Vec<String>, SealError> {
+    let mut paths = fs::read_dir(Path::new(dir))?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_str().is_some_and(|n| n.ends_with(extension) && is_puzzle_input(n)))
+        .map(|e| e.path().to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// `day<n>.txt`, the name the runner and the report look inputs up by, or its sealed copy.
+fn is_puzzle_input(name: &str) -> bool {
+    name.strip_suffix(SEALED_EXTENSION).unwrap_or(name)
+        .strip_prefix("day")
+        .and_then(|n| n.strip_suffix(".txt"))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_round_trip_sealed_input() {
+        let secret = Secret::from_passphrase("hunter2");
+        let sealed = seal_bytes(b"Time: 7 15 30\nDistance: 9 40 200", &secret).unwrap();
+
+        assert!(is_sealed(&sealed));
+        assert_eq!(unseal_bytes(&sealed, &secret).unwrap(), b"Time: 7 15 30\nDistance: 9 40 200");
+    }
+
+    #[test]
+    fn should_reject_wrong_passphrase() {
+        let sealed = seal_bytes(b"LR", &Secret::from_passphrase("hunter2")).unwrap();
+
+        assert!(matches!(unseal_bytes(&sealed, &Secret::from_passphrase("hunter3")), Err(SealError::Decrypt)));
+    }
+
+    #[test]
+    fn should_seal_and_unseal_dir() {
+        let dir = std::env::temp_dir().join(format!("aoc2023-seal-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let plain_path = dir.join("day8.txt");
+        fs::write(&plain_path, "LR\n").unwrap();
+        fs::write(dir.join("day8_test.txt"), "RL\n").unwrap();
+        let secret = Secret::from_passphrase("hunter2");
+
+        assert_eq!(seal_dir(dir.to_str().unwrap(), &secret).unwrap().len(), 1);
+        assert!(!plain_path.exists());
+        assert!(dir.join("day8_test.txt").exists());
+        assert_eq!(unseal_dir(dir.to_str().unwrap(), &secret).unwrap(), vec![plain_path.to_string_lossy().to_string()]);
+        assert_eq!(fs::read_to_string(&plain_path).unwrap(), "LR\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_reject_plaintext_input() {
+        assert!(matches!(unseal_bytes(b"32T3K 765", &Secret::from_passphrase("hunter2")), Err(SealError::NotSealed)));
+    }
+}
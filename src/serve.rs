@@ -0,0 +1,128 @@
+use std::io::Read;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use tiny_http::{Header, Method, Request, Response, Server};
+use crate::cache::{run_solver, AnswerCache};
+use crate::solutions::{Outcome, Solver};
+
+/// Larger than any real puzzle input by a wide margin, but keeps a stray upload from exhausting memory.
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Serves `POST /2023/day/{n}/part/{p}` with the puzzle input as the body, answering with JSON.
+/// When several solvers share a day/part, the first registered one (the built-in) answers.
+///
+/// Requests are handled one at a time on the calling thread: solvers (plugins included) aren't `Send`
+/// and share the answer cache, so a slow input holds up everything queued behind it.
+pub(crate) fn serve(address: &str, solvers: &[Solver], cache: &mut Option<AnswerCache>) {
+    let server = Server::http(address).unwrap_or_else(|e| panic!("Could not listen on {}: {}", address, e));
+    println!("Listening on http://{}", address);
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = handle(&mut request, solvers, cache);
+        println!("{} {} -> {}", request.method(), request.url(), status);
+
+        let header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+        let response = Response::from_string(body).with_status_code(status).with_header(header);
+        _ = request.respond(response);
+    }
+}
+
+fn handle(request: &mut Request, solvers: &[Solver], cache: &mut Option<AnswerCache>) -> (u16, String) {
+    let Some((day, part)) = parse_route(request.url()) else {
+        return (404, error_json("expected /2023/day/{n}/part/{p}"));
+    };
+
+    if request.method() != &Method::Post {
+        return (405, error_json("puzzle input must be POSTed"));
+    }
+
+    let Some(solver) = solvers.iter().find(|s| s.day == day && s.part == part) else {
+        return (404, error_json(&format!("no solver for day {} part {}", day, part)));
+    };
+
+    if request.body_length().is_some_and(|length| length > MAX_BODY_BYTES) {
+        return (413, error_json(&format!("puzzle input must be at most {} bytes", MAX_BODY_BYTES)));
+    }
+
+    // Chunked bodies don't declare a length up front, so the read itself is capped too.
+    let mut body = vec![];
+    if request.as_reader().take(MAX_BODY_BYTES as u64 + 1).read_to_end(&mut body).is_err() {
+        return (400, error_json("could not read the request body"));
+    }
+    if body.len() > MAX_BODY_BYTES {
+        return (413, error_json(&format!("puzzle input must be at most {} bytes", MAX_BODY_BYTES)));
+    }
+    let Ok(i) = String::from_utf8(body) else {
+        return (400, error_json("puzzle input must be valid UTF-8"));
+    };
+
+    // Solvers unwrap on malformed input, so a panic is reported as a bad request rather than taking the server down.
+    match catch_unwind(AssertUnwindSafe(|| run_solver(cache, solver, &i))) {
+        Ok(outcome) => (200, outcome_json(day, part, &outcome)),
+        Err(_) => (422, error_json(&format!("day {} part {} could not solve the given input", day, part))),
+    }
+}
+
+fn outcome_json(day: u8, part: u8, outcome: &Outcome) -> String {
+    format!(
+        "{{\"day\":{},\"part\":{},\"answer\":\"{}\",\"elapsed_micros\":{},\"cached\":{}}}",
+        day, part, escape_json(&outcome.answer), outcome.elapsed.as_micros(), outcome.cached
+    )
+}
+
+fn error_json(message: &str) -> String {
+    format!("{{\"error\":\"{}\"}}", escape_json(message))
+}
+
+fn escape_json(s: &str) -> String {
+    s.chars().fold(String::new(), |mut acc, c| {
+        match c {
+            '"' => acc.push_str("\\\""),
+            '\\' => acc.push_str("\\\\"),
+            '\n' => acc.push_str("\\n"),
+            c if c.is_control() => acc.push_str(&format!("\\u{:04x}", c as u32)),
+            c => acc.push(c),
+        }
+        acc
+    })
+}
+
+/// Routes on the path alone, so a query string like `?source=editor` doesn't change which solver answers.
+fn parse_route(url: &str) -> Option<(u8, u8)> {
+    let path = url.split_once('?').map_or(url, |(path, _)| path);
+    let segments = path.trim_matches('/').split('/').collect::<Vec<&str>>();
+    match segments[..] {
+        ["2023", "day", day, "part", part] => Some((day.parse().ok()?, part.parse().ok()?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn should_parse_solver_route() {
+        assert_eq!(parse_route("/2023/day/5/part/2"), Some((5, 2)));
+        assert_eq!(parse_route("/2023/day/12/part/1/"), Some((12, 1)));
+        assert_eq!(parse_route("/2023/day/5/part/2?source=editor"), Some((5, 2)));
+        assert_eq!(parse_route("/2023/day/5/part/2/?"), Some((5, 2)));
+        assert_eq!(parse_route("/2022/day/5/part/2"), None);
+        assert_eq!(parse_route("/2023/day/five/part/2"), None);
+        assert_eq!(parse_route("/"), None);
+    }
+
+    #[test]
+    fn should_format_outcome_as_json() {
+        let outcome = Outcome { answer: "6440".to_owned(), elapsed: Duration::from_micros(42), cached: true };
+        assert_eq!(
+            outcome_json(7, 1, &outcome),
+            r#"{"day":7,"part":1,"answer":"6440","elapsed_micros":42,"cached":true}"#
+        );
+    }
+
+    #[test]
+    fn should_escape_json_strings() {
+        assert_eq!(error_json("bad \"input\"\n"), r#"{"error":"bad \"input\"\n"}"#);
+    }
+}
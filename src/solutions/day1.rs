@@ -1,36 +1,430 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use aho_corasick::AhoCorasick;
-
-pub (crate) fn day1(path: String) {
-    let file = File::open(path).unwrap();
-    let buffer = BufReader::new(file);
-    let patterns = [
-        "one", "two", "three", "four", "five",
-        "six", "seven", "eight", "nine",
-        "1", "2", "3", "4", "5", "6", "7", "8", "9"
-    ];
-
-    let ac = AhoCorasick::new(patterns).unwrap();
-    let total = buffer.lines().fold(0, |acc, l| acc + parse_calibration_string(&ac, &l.unwrap()));
-    println!("Sum of calibration values: {}", total);
-}
-
-pub(crate) fn parse_calibration_string(ac: &AhoCorasick, input: &str ) -> u32 {
-    let matches: Vec<u32> = ac.find_overlapping_iter(input).map(|mat| {
-        let pattern_idx = mat.pattern().as_u32();
-        match pattern_idx {
-            0..=8 => pattern_idx + 1,
-            9..=17 => pattern_idx - 8,
-            _ => 0
-        }
-    }).collect();
-
-    let num = match matches.len() {
-        0 => (0, 0),
-        1 => (matches[0], matches[0]),
-        n => (matches[0], matches[n - 1])
-    };
-
-    num.0 * 10 + num.1
+use std::fmt::{Display, Formatter};
+use std::fs;
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
+use clap::ValueEnum;
+use crate::utilities::input::line_chunks;
+
+const DIGITS: [(&str, u32); 9] = [
+    ("1", 1), ("2", 2), ("3", 3), ("4", 4), ("5", 5),
+    ("6", 6), ("7", 7), ("8", 8), ("9", 9),
+];
+
+/// The words part 2 reads. The puzzle spells out one to nine only, so `zero` is left as letters.
+const PUZZLE_WORDS: [(&str, u32); 9] = [
+    ("one", 1), ("two", 2), ("three", 3), ("four", 4), ("five", 5),
+    ("six", 6), ("seven", 7), ("eight", 8), ("nine", 9),
+];
+
+const ENGLISH: [(&str, u32); 28] = [
+    ("zero", 0), ("one", 1), ("two", 2), ("three", 3), ("four", 4),
+    ("five", 5), ("six", 6), ("seven", 7), ("eight", 8), ("nine", 9),
+    ("ten", 10), ("eleven", 11), ("twelve", 12), ("thirteen", 13), ("fourteen", 14),
+    ("fifteen", 15), ("sixteen", 16), ("seventeen", 17), ("eighteen", 18), ("nineteen", 19),
+    ("twenty", 20), ("thirty", 30), ("forty", 40), ("fifty", 50),
+    ("sixty", 60), ("seventy", 70), ("eighty", 80), ("ninety", 90),
+];
+
+const GERMAN: [(&str, u32); 10] = [
+    ("null", 0), ("eins", 1), ("zwei", 2), ("drei", 3), ("vier", 4),
+    ("fünf", 5), ("sechs", 6), ("sieben", 7), ("acht", 8), ("neun", 9),
+];
+
+const FRENCH: [(&str, u32); 10] = [
+    ("zéro", 0), ("un", 1), ("deux", 2), ("trois", 3), ("quatre", 4),
+    ("cinq", 5), ("six", 6), ("sept", 7), ("huit", 8), ("neuf", 9),
+];
+
+const SPANISH: [(&str, u32); 10] = [
+    ("cero", 0), ("uno", 1), ("dos", 2), ("tres", 3), ("cuatro", 4),
+    ("cinco", 5), ("seis", 6), ("siete", 7), ("ocho", 8), ("nueve", 9),
+];
+
+pub(crate) fn part1(i: &str) -> u64 {
+    calibrate(i, &CalibrationPatterns::digits())
+}
+
+pub(crate) fn part2(i: &str) -> u64 {
+    calibrate(i, &CalibrationPatterns::digits_and_words())
+}
+
+pub(crate) fn calibrate(i: &str, patterns: &CalibrationPatterns) -> u64 {
+    i.lines().fold(0, |acc, l| acc.saturating_add(parse_calibration_string(patterns, l)))
+}
+
+/// Splits `i` at line boundaries into one chunk per thread, calibrating each chunk on its own
+/// thread against the same patterns and summing their totals.
+pub(crate) fn calibrate_parallel(i: &str, patterns: &CalibrationPatterns, threads: usize) -> u64 {
+    let threads = threads.max(1);
+    if threads == 1 {
+        return calibrate(i, patterns);
+    }
+
+    std::thread::scope(|scope| {
+        line_chunks(i, i.len().div_ceil(threads))
+            .map(|chunk| scope.spawn(move || calibrate(chunk, patterns)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .fold(0, u64::saturating_add)
+    })
+}
+
+#[derive(Debug)]
+pub(crate) enum VocabularyError {
+    Io(std::io::Error),
+    Malformed(usize),
+}
+
+impl Display for VocabularyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VocabularyError::Io(e) => write!(f, "{}", e),
+            VocabularyError::Malformed(line) => write!(f, "line {} should be `<word> <value>`", line),
+        }
+    }
+}
+
+impl From<std::io::Error> for VocabularyError {
+    fn from(value: std::io::Error) -> Self {
+        VocabularyError::Io(value)
+    }
+}
+
+/// Number words and the value each one stands for.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Vocabulary {
+    words: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    pub fn english() -> Self {
+        Self::from_table(&ENGLISH)
+    }
+
+    /// One of the built-in vocabularies: `en`, `de`, `fr` or `es`.
+    pub fn builtin(language: &str) -> Option<Self> {
+        match language {
+            "en" => Some(Self::english()),
+            "de" => Some(Self::from_table(&GERMAN)),
+            "fr" => Some(Self::from_table(&FRENCH)),
+            "es" => Some(Self::from_table(&SPANISH)),
+            _ => None,
+        }
+    }
+
+    /// A built-in vocabulary by name, or else one read from the file at `name`.
+    pub fn load(name: &str) -> Result<Self, VocabularyError> {
+        match Self::builtin(name) {
+            Some(vocabulary) => Ok(vocabulary),
+            None => Self::parse(&fs::read_to_string(name)?),
+        }
+    }
+
+    /// One `<word> <value>` pair per line. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(table: &str) -> Result<Self, VocabularyError> {
+        let words = table.lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+            .map(|(idx, l)| match l.split_whitespace().collect::<Vec<&str>>()[..] {
+                [word, value] => value.parse().map(|v| (word.to_owned(), v)).map_err(|_| VocabularyError::Malformed(idx + 1)),
+                _ => Err(VocabularyError::Malformed(idx + 1)),
+            })
+            .collect::<Result<Vec<(String, u32)>, VocabularyError>>()?;
+
+        Ok(Self { words })
+    }
+
+    fn from_table(table: &[(&str, u32)]) -> Self {
+        Self { words: table.iter().map(|(w, v)| (w.to_string(), *v)).collect() }
+    }
+}
+
+/// How a line's value is read from the numbers on it.
+#[derive(Copy, Clone, Debug, PartialEq, ValueEnum)]
+pub(crate) enum Extraction {
+    /// The first and last single digits as a two-digit number, as in the puzzle
+    FirstLastDigit,
+    /// The first and last whole numbers written one after the other
+    FirstLastNumber,
+    /// Every whole number on the line added together
+    SumOfNumbers,
+}
+
+/// The tokens that count as numbers on a calibration line, the value each one stands for, and
+/// how a line's value is read from them.
+pub(crate) struct CalibrationPatterns {
+    automaton: AhoCorasick,
+    values: Vec<u32>,
+    extraction: Extraction,
+}
+
+impl CalibrationPatterns {
+    /// Ignoring case covers any mix of case for ASCII letters, but accented letters only match
+    /// when the word is written all in lower or all in upper case.
+    pub fn new(patterns: &[(String, u32)], ignore_case: bool) -> Self {
+        let mut patterns = patterns.to_vec();
+        if ignore_case {
+            patterns.extend(patterns.iter().filter(|(p, _)| !p.is_ascii()).map(|(p, v)| (p.to_uppercase(), *v)).collect::<Vec<_>>());
+        }
+
+        Self {
+            automaton: AhoCorasickBuilder::new()
+                .ascii_case_insensitive(ignore_case)
+                .build(patterns.iter().map(|(p, _)| p))
+                .unwrap(),
+            values: patterns.iter().map(|(_, v)| *v).collect(),
+            extraction: Extraction::FirstLastDigit,
+        }
+    }
+
+    pub fn digits() -> Self {
+        Self::new(&Vocabulary::from_table(&DIGITS).words, false)
+    }
+
+    pub fn digits_and_words() -> Self {
+        Self::with_words(&Vocabulary::from_table(&PUZZLE_WORDS), false)
+    }
+
+    pub fn with_words(vocabulary: &Vocabulary, ignore_case: bool) -> Self {
+        Self::new(&[Vocabulary::from_table(&DIGITS).words, vocabulary.words.clone()].concat(), ignore_case)
+    }
+
+    pub fn with_extraction(mut self, extraction: Extraction) -> Self {
+        self.extraction = extraction;
+        self
+    }
+}
+
+/// A numeral or number word found on a line, and the byte offset it starts at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct CalibrationMatch<'a> {
+    pub offset: usize,
+    pub text: &'a str,
+    pub value: u64,
+}
+
+impl CalibrationMatch<'_> {
+    fn end(&self) -> usize {
+        self.offset + self.text.len()
+    }
+}
+
+/// The matches on a line that the extraction rule reads, and the value it read from them.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Explanation<'a> {
+    pub line_number: usize,
+    pub line: &'a str,
+    pub extraction: Extraction,
+    pub matches: Vec<CalibrationMatch<'a>>,
+    pub value: u64,
+}
+
+impl<'a> Explanation<'a> {
+    pub fn first(&self) -> Option<&CalibrationMatch<'a>> {
+        self.matches.first()
+    }
+
+    pub fn last(&self) -> Option<&CalibrationMatch<'a>> {
+        self.matches.last()
+    }
+}
+
+impl Display for Explanation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "line {}: {:?} = {}", self.line_number, self.line, self.value)?;
+        for m in self.matches.iter() {
+            writeln!(f, "  {:>5}  {:?} -> {}", m.offset, m.text, m.value)?;
+        }
+        match (self.extraction, self.first(), self.last()) {
+            (_, None, _) | (_, _, None) => writeln!(f, "  no numbers found")?,
+            (Extraction::SumOfNumbers, _, _) => writeln!(f, "  sum of {} number(s)", self.matches.len())?,
+            (_, Some(first), Some(last)) => writeln!(f, "  first {:?} at {}, last {:?} at {}", first.text, first.offset, last.text, last.offset)?,
+        }
+        if self.value == 0 {
+            writeln!(f, "  ⚠ contributes 0")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Explains how each line of a calibration document was read.
+pub(crate) fn explain<'a>(i: &'a str, patterns: &'a CalibrationPatterns) -> impl Iterator<Item = Explanation<'a>> + 'a {
+    i.lines().enumerate().map(|(idx, line)| {
+        let matches = find_matches(patterns, line);
+        let value = value_of(patterns.extraction, &matches);
+        Explanation { line_number: idx + 1, line, extraction: patterns.extraction, matches, value }
+    })
+}
+
+/// The matches the extraction rule reads, in the order they appear on the line.
+fn find_matches<'a>(patterns: &CalibrationPatterns, line: &'a str) -> Vec<CalibrationMatch<'a>> {
+    let overlapping = patterns.automaton
+        .find_overlapping_iter(line)
+        .map(|mat| CalibrationMatch { offset: mat.start(), text: &line[mat.range()], value: patterns.values[mat.pattern().as_usize()] as u64 });
+
+    match patterns.extraction {
+        Extraction::FirstLastDigit => overlapping.filter(|m| m.value < 10).collect(),
+        Extraction::FirstLastNumber | Extraction::SumOfNumbers => {
+            let words = overlapping.filter(|m| !m.text.starts_with(|c: char| c.is_ascii_digit()));
+            whole_numbers(join_compounds(line, words.chain(numerals(line)).collect()))
+        }
+    }
+}
+
+/// Runs of ASCII digits, read as one number each and saturating rather than overflowing.
+fn numerals(line: &str) -> impl Iterator<Item = CalibrationMatch<'_>> {
+    line.match_indices(|c: char| c.is_ascii_digit())
+        .map(|(offset, _)| offset)
+        .filter(move |offset| *offset == 0 || !line.as_bytes()[offset - 1].is_ascii_digit())
+        .map(move |offset| {
+            let text = line[offset..].split(|c: char| !c.is_ascii_digit()).next().unwrap_or_default();
+            let value = text.bytes().fold(0u64, |acc, b| acc.saturating_mul(10).saturating_add((b - b'0') as u64));
+            CalibrationMatch { offset, text, value }
+        })
+}
+
+/// Joins a tens word, a hyphen and a units word into one match, so `twenty-one` reads as 21.
+fn join_compounds<'a>(line: &'a str, mut matches: Vec<CalibrationMatch<'a>>) -> Vec<CalibrationMatch<'a>> {
+    let units = matches.iter().filter(|m| (1..10).contains(&m.value)).copied().collect::<Vec<_>>();
+    let compounds = matches.iter()
+        .filter(|tens| (20..100).contains(&tens.value) && tens.value % 10 == 0 && line[tens.end()..].starts_with('-'))
+        .flat_map(|tens| units.iter()
+            .filter(|unit| unit.offset == tens.end() + 1)
+            .map(|unit| CalibrationMatch { offset: tens.offset, text: &line[tens.offset..unit.end()], value: tens.value + unit.value }))
+        .collect::<Vec<_>>();
+
+    matches.extend(compounds);
+    matches
+}
+
+/// Drops every match lying inside a longer one, such as `seven` in `seventeen`, keeping the rest
+/// in order. Matches that only overlap, like `eight` and `two` in `eightwo`, are both kept.
+fn whole_numbers(mut matches: Vec<CalibrationMatch>) -> Vec<CalibrationMatch> {
+    matches.sort_by_key(|m| (m.offset, std::cmp::Reverse(m.text.len())));
+    let mut kept: Vec<CalibrationMatch> = vec![];
+    for m in matches {
+        if kept.last().is_none_or(|k| m.end() > k.end()) {
+            kept.push(m);
+        }
+    }
+
+    kept
+}
+
+/// The value of a line under the patterns' extraction rule, or 0 if there are no numbers on it.
+/// Spelled-out numbers may overlap, so `eightwo` reads as 8 then 2.
+pub(crate) fn parse_calibration_string(patterns: &CalibrationPatterns, input: &str) -> u64 {
+    value_of(patterns.extraction, &find_matches(patterns, input))
+}
+
+fn value_of(extraction: Extraction, matches: &[CalibrationMatch]) -> u64 {
+    let (Some(first), Some(last)) = (matches.first(), matches.last()) else { return 0 };
+    match extraction {
+        Extraction::FirstLastDigit => first.value * 10 + last.value,
+        Extraction::FirstLastNumber => {
+            let shift = 10u64.saturating_pow(last.value.checked_ilog10().unwrap_or(0) + 1);
+            first.value.saturating_mul(shift).saturating_add(last.value)
+        }
+        Extraction::SumOfNumbers => matches.iter().fold(0, |acc, m| acc.saturating_add(m.value)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_only_read_numerals_in_part1() {
+        let input = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet";
+        assert_eq!(part1(input), 142);
+        assert_eq!(parse_calibration_string(&CalibrationPatterns::digits(), "two1nine"), 11);
+    }
+
+    #[test]
+    fn should_read_overlapping_words_in_part2() {
+        let input = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen";
+        assert_eq!(part2(input), 281);
+        assert_eq!(parse_calibration_string(&CalibrationPatterns::digits_and_words(), "eightwo"), 82);
+    }
+
+    #[test]
+    fn part2_should_not_read_zero() {
+        assert_eq!(part2("zero5"), 55);
+        let english = CalibrationPatterns::with_words(&Vocabulary::english(), false);
+        assert_eq!(parse_calibration_string(&english, "zero5"), 5);
+    }
+
+    #[test]
+    fn should_decode_other_languages() {
+        let german = CalibrationPatterns::with_words(&Vocabulary::builtin("de").unwrap(), false);
+        assert_eq!(parse_calibration_string(&german, "xzweifünfachtx"), 28);
+        let french = CalibrationPatterns::with_words(&Vocabulary::builtin("fr").unwrap(), false);
+        assert_eq!(parse_calibration_string(&french, "zérotroisquatre"), 4);
+        let spanish = CalibrationPatterns::with_words(&Vocabulary::builtin("es").unwrap(), false);
+        assert_eq!(parse_calibration_string(&spanish, "ochonueve7"), 87);
+    }
+
+    #[test]
+    fn should_match_words_ignoring_case() {
+        let patterns = CalibrationPatterns::with_words(&Vocabulary::builtin("de").unwrap(), true);
+        assert_eq!(parse_calibration_string(&patterns, "DreiFÜNF"), 35);
+        assert_eq!(parse_calibration_string(&CalibrationPatterns::digits_and_words(), "ONE2"), 22);
+    }
+
+    #[test]
+    fn should_parse_vocabulary_table() {
+        let vocabulary = Vocabulary::parse("# Dutch\nnul 0\neen 1\n\ntwee 2").unwrap();
+        assert_eq!(parse_calibration_string(&CalibrationPatterns::with_words(&vocabulary, false), "tweeeen"), 21);
+        assert!(matches!(Vocabulary::parse("een\ntwee 2"), Err(VocabularyError::Malformed(1))));
+        assert!(matches!(Vocabulary::parse("een one"), Err(VocabularyError::Malformed(1))));
+    }
+
+    #[test]
+    fn parallel_calibration_should_match_sequential() {
+        let patterns = CalibrationPatterns::digits_and_words();
+        let input = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen\n".repeat(50);
+
+        for threads in [1, 2, 3, 8, 1000] {
+            assert_eq!(calibrate_parallel(&input, &patterns, threads), 281 * 50);
+        }
+        assert_eq!(calibrate_parallel("", &patterns, 4), 0);
+    }
+
+    #[test]
+    fn should_explain_overlapping_matches() {
+        let patterns = CalibrationPatterns::digits_and_words();
+        let explanations = explain("eightwothree\nabc", &patterns).collect::<Vec<_>>();
+
+        let offsets = explanations[0].matches.iter().map(|m| (m.offset, m.text, m.value)).collect::<Vec<_>>();
+        assert_eq!(offsets, vec![(0, "eight", 8), (4, "two", 2), (7, "three", 3)]);
+        assert_eq!(explanations[0].first().map(|m| m.text), Some("eight"));
+        assert_eq!(explanations[0].last().map(|m| m.text), Some("three"));
+        assert_eq!(explanations[0].value, 83);
+
+        assert_eq!((explanations[1].line_number, explanations[1].value), (2, 0));
+        assert!(explanations[1].to_string().contains("no numbers found"));
+        assert!(explanations[1].to_string().contains("contributes 0"));
+    }
+
+    #[test]
+    fn should_read_whole_numbers() {
+        let first_last = CalibrationPatterns::with_words(&Vocabulary::english(), false).with_extraction(Extraction::FirstLastNumber);
+        assert_eq!(parse_calibration_string(&first_last, "twenty-one x 305 y seventeen"), 2117);
+        assert_eq!(parse_calibration_string(&first_last, "a42b"), 4242);
+        assert_eq!(parse_calibration_string(&first_last, "eightwo"), 82);
+        assert_eq!(parse_calibration_string(&first_last, "ninety-ten"), 9010);
+
+        let sum = CalibrationPatterns::with_words(&Vocabulary::english(), false).with_extraction(Extraction::SumOfNumbers);
+        assert_eq!(parse_calibration_string(&sum, "twenty-one x 305 y seventeen"), 343);
+        assert_eq!(parse_calibration_string(&sum, "fortytwo 10"), 52);
+        assert_eq!(parse_calibration_string(&sum, "99999999999999999999 1"), u64::MAX);
+    }
+
+    #[test]
+    fn digit_extraction_should_ignore_larger_words() {
+        let patterns = CalibrationPatterns::with_words(&Vocabulary::english(), false);
+        assert_eq!(parse_calibration_string(&patterns, "twenty-seventeen"), 77);
+        assert_eq!(parse_calibration_string(&patterns, "x10y"), 11);
+    }
 }
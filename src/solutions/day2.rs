@@ -1,114 +1,266 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-pub(crate) fn day2(path: String) {
-    let file = File::open(path).unwrap();
-    let buffer = BufReader::new(file);
-    let given_set = HashMap::from([
-        (Colour::Red, 12),
-        (Colour::Green, 13),
-        (Colour::Blue, 14),
-    ]);
-
-    let mut total_of_ids = 0;
-    let mut power_of_minimums = 0;
-    for lines in buffer.lines() {
-        let l = lines.unwrap();
-        let game_data = GameData::new_from_line(l).unwrap();
-        let x = is_game_valid_for_given_set(&game_data, &given_set);
-        total_of_ids += x.unwrap_or(0);
-
-        let y = get_power_of_minimum_required(&game_data);
-        power_of_minimums += y;
-    }
-
-    println!("Total of possible game Ids: {}", total_of_ids);
-    println!("Power of minimums: {}", power_of_minimums);
-
-}
-
-#[derive(Eq, PartialEq, Hash, Clone)]
-enum Colour {
-    Red,
-    Green,
-    Blue,
-    None
-}
-
-impl From<&str> for Colour {
-    fn from(value: &str) -> Self {
-        match value.to_lowercase().as_ref() {
-            "red" => Colour::Red,
-            "green" => Colour::Green,
-            "blue" => Colour::Blue,
-            _ => Colour::None
+use std::fmt::{Display, Formatter};
+use std::fs;
+use itertools::Itertools;
+use nom::bytes::complete::tag;
+use nom::character::complete::{alpha1, char, digit1, space0, space1};
+use nom::combinator::{cut, eof, map_res};
+use nom::error::{context, VerboseError, VerboseErrorKind};
+use nom::IResult;
+use nom::multi::separated_list1;
+use nom::sequence::{pair, preceded, separated_pair};
+use crate::utilities::input::{Input, InputError};
+use crate::utilities::math::ln_binomial;
+
+/// The bag the puzzle asks about.
+pub(crate) const PUZZLE_BAG: &str = "red=12,green=13,blue=14";
+
+pub(crate) fn part1(i: &str) -> u32 {
+    let mut colours = Colours::new();
+    let given_set = Bag::parse(PUZZLE_BAG, &mut colours).unwrap();
+
+    games(i, &mut colours)
+        .filter_map(|game| is_game_valid_for_given_set(&game.unwrap(), &given_set))
+        .sum()
+}
+
+pub(crate) fn part2(i: &str) -> u32 {
+    let mut colours = Colours::new();
+    let cubes = [colours.intern("red"), colours.intern("green"), colours.intern("blue")];
+
+    games(i, &mut colours)
+        .map(|game| get_power_of_minimum_required(&game.unwrap(), &cubes))
+        .sum()
+}
+
+/// Parses each line of the input as a game as it's reached, so the parts only ever hold one game.
+fn games<'a>(i: &'a str, colours: &'a mut Colours) -> impl Iterator<Item = Result<GameData, GameError>> + 'a {
+    i.lines()
+        .enumerate()
+        .map(|(idx, l)| GameData::parse(l, colours).map_err(|e| GameError { line: idx + 1, ..e }))
+}
+
+/// Parses every line of the input as a game, sharing `colours` so bags can name the same colours.
+pub(crate) fn parse_games(i: &str, colours: &mut Colours) -> Result<Vec<GameData>, GameError> {
+    games(i, colours).collect()
+}
+
+/// How many of `games` were possible with `bag`, and the sum of their ids.
+pub(crate) fn possible_games(games: &[GameData], bag: &Bag) -> (usize, u32) {
+    games.iter()
+        .filter_map(|game| is_game_valid_for_given_set(game, bag))
+        .fold((0, 0), |(count, sum), id| (count + 1, sum + id))
+}
+
+/// What the day 2 subcommands work on: the games of an input and the bags to weigh them against,
+/// with the colours of both interned together.
+pub(crate) struct GameLog {
+    pub colours: Colours,
+    pub games: Vec<GameData>,
+    pub bags: Vec<Bag>,
+}
+
+#[derive(Debug)]
+pub(crate) enum GameLogError {
+    Input(InputError),
+    Bags(BagError),
+    Games(GameError),
+}
+
+impl Display for GameLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameLogError::Input(e) => write!(f, "input: {}", e),
+            GameLogError::Bags(e) => write!(f, "bags: {}", e),
+            GameLogError::Games(e) => write!(f, "games: {}", e),
         }
     }
 }
 
-struct GameData {
-    pub id: u32,
-    pub revealed: Vec<Vec<BallData>>
+impl From<InputError> for GameLogError {
+    fn from(value: InputError) -> Self {
+        GameLogError::Input(value)
+    }
 }
 
-impl GameData {
-    pub fn new_from_raw_data(id: u32, revealed:Vec<&str>) -> Self {
-        let raw_ball_data: Vec<Vec<&str>> = revealed.iter().map(|rev| rev.split(", ").collect::<Vec<&str>>()).collect();
-        let ball_data: Vec<Vec<BallData>> = raw_ball_data.iter().map(|raw| raw.iter().map(|data| BallData::from(*data)).collect()).collect();
+impl From<BagError> for GameLogError {
+    fn from(value: BagError) -> Self {
+        GameLogError::Bags(value)
+    }
+}
 
-        Self {
-            id,
-            revealed: ball_data
+impl From<GameError> for GameLogError {
+    fn from(value: GameError) -> Self {
+        GameLogError::Games(value)
+    }
+}
+
+impl GameLog {
+    /// Reads the bags first, see [`load_bags`], then every game of the input at `input_path`.
+    pub fn load(input_path: &str, specs: &[String], bags_path: Option<&str>) -> Result<Self, GameLogError> {
+        let mut colours = Colours::new();
+        let bags = load_bags(specs, bags_path, &mut colours)?;
+        let games = parse_games(&Input::open(input_path)?, &mut colours)?;
+
+        Ok(Self { colours, games, bags })
+    }
+}
+
+/// The bags given as `specs` followed by any in the file at `path`, or just the puzzle's bag
+/// when there are neither.
+fn load_bags(specs: &[String], path: Option<&str>, colours: &mut Colours) -> Result<Vec<Bag>, BagError> {
+    let mut bags = specs.iter().map(|spec| Bag::parse(spec, colours)).collect::<Result<Vec<Bag>, BagError>>()?;
+    if let Some(path) = path {
+        bags.extend(Bag::load(path, colours)?);
+    }
+    if bags.is_empty() {
+        bags.push(Bag::parse(PUZZLE_BAG, colours)?);
+    }
+
+    Ok(bags)
+}
+
+#[derive(Debug)]
+pub(crate) enum BagError {
+    Io(std::io::Error),
+    Malformed(String),
+}
+
+impl Display for BagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BagError::Io(e) => write!(f, "{}", e),
+            BagError::Malformed(entry) => write!(f, "{:?} should be `<colour>=<count>`", entry),
         }
     }
+}
+
+impl From<std::io::Error> for BagError {
+    fn from(value: std::io::Error) -> Self {
+        BagError::Io(value)
+    }
+}
 
-    pub fn new_from_line (line: String) -> Option<Self> {
-        let parts: Vec<&str> = line.split(&[':', ';'][..]).collect();
+/// How many cubes of each colour a bag holds. Any colour it doesn't name, it has none of.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Bag {
+    pub label: String,
+    cubes: HashMap<Colour, u32>,
+}
 
-        let id_str: &str = parts
-            .first()?
-            .split(" ")
-            .skip(1).
-            take(1)
-            .collect::<Vec<&str>>()
-            .first()?;
+impl Bag {
+    /// Parses `<colour>=<count>` pairs separated by commas, e.g. `red=12,green=13,blue=14`.
+    pub fn parse(spec: &str, colours: &mut Colours) -> Result<Self, BagError> {
+        let cubes = spec.split(',')
+            .map(|entry| match entry.split_once('=') {
+                Some((colour, count)) if !colour.trim().is_empty() => count.trim()
+                    .parse()
+                    .map(|count| (colours.intern(colour.trim()), count))
+                    .map_err(|_| BagError::Malformed(entry.to_owned())),
+                _ => Err(BagError::Malformed(entry.to_owned())),
+            })
+            .collect::<Result<HashMap<Colour, u32>, BagError>>()?;
 
-        let id = id_str.parse::<u32>().ok()?;
+        Ok(Self { label: spec.trim().to_owned(), cubes })
+    }
 
-        let revealed_sets_parts = &parts[1..];
-        Some(GameData::new_from_raw_data(id, revealed_sets_parts.to_vec()))
+    /// One bag per line, skipping blank lines and lines starting with `#`.
+    pub fn load(path: &str, colours: &mut Colours) -> Result<Vec<Self>, BagError> {
+        fs::read_to_string(path)?
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+            .map(|l| Self::parse(l, colours))
+            .collect()
     }
 
-    fn get_max_colours_seen(&self) -> HashMap<Colour, u32> {
-        self.revealed.iter().fold(HashMap::new(), |mut acc: HashMap<Colour, u32>, data: &Vec<BallData>| {
-            for ball_data in data {
-                if !acc.contains_key(&ball_data.colour) {
-                    acc.insert(ball_data.colour.clone(), ball_data.count);
-                } else {
-                    let val = acc.get_mut(&ball_data.colour).unwrap();
-                    if *val < ball_data.count {
-                        *val = ball_data.count
-                    }
-                }
-            }
+    pub fn count_of(&self, colour: Colour) -> u32 {
+        self.cubes.get(&colour).copied().unwrap_or(0)
+    }
+
+    pub fn colours(&self) -> impl Iterator<Item = Colour> + '_ {
+        self.cubes.keys().copied()
+    }
+}
+
+/// A colour name interned by [`Colours`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub(crate) struct Colour(usize);
+
+/// Colour names interned to dense [`Colour`] ids. Names are compared ignoring case.
+#[derive(Debug, Default)]
+pub(crate) struct Colours {
+    names: Vec<String>,
+    ids: HashMap<String, Colour>,
+}
+
+impl Colours {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id for `name`, adding it if it's new.
+    pub fn intern(&mut self, name: &str) -> Colour {
+        let name = name.to_lowercase();
+        if let Some(colour) = self.ids.get(&name) {
+            return *colour;
+        }
 
+        let colour = Colour(self.names.len());
+        self.names.push(name.clone());
+        self.ids.insert(name, colour);
+        colour
+    }
+
+    pub fn id(&self, name: &str) -> Option<Colour> {
+        self.ids.get(&name.to_lowercase()).copied()
+    }
+
+    pub fn name(&self, colour: Colour) -> &str {
+        &self.names[colour.0]
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GameData {
+    pub id: u32,
+    pub revealed: Vec<Vec<BallData>>
+}
+
+impl GameData {
+    /// Parses `Game <id>: <count> <colour>, ...; ...`, keeping each reveal's draws together.
+    pub fn parse(line: &str, colours: &mut Colours) -> Result<Self, GameError> {
+        let (_, (id, reveals)) = parse_game(line).map_err(|e| GameError::at(line, e))?;
+        let revealed = reveals.into_iter()
+            .map(|draws| draws.into_iter().map(|(count, colour)| BallData::new(colours.intern(colour), count)).collect())
+            .collect();
+
+        Ok(Self { id, revealed })
+    }
+
+    /// The most of each colour seen in any one reveal. Colours never shown are left out.
+    pub fn get_max_colours_seen(&self) -> HashMap<Colour, u32> {
+        self.revealed.iter().flatten().fold(HashMap::new(), |mut acc, ball_data| {
+            let max = acc.entry(ball_data.colour).or_insert(0);
+            *max = (*max).max(ball_data.count);
             acc
         })
     }
 
-    pub fn is_possible(&self, given_set: &HashMap<Colour, u32>) -> Option<bool> {
-        let max_seen = self.get_max_colours_seen();
-        let is_possible = given_set.get(&Colour::Red)? >= max_seen.get(&Colour::Red)?
-            && given_set.get(&Colour::Blue)? >= max_seen.get(&Colour::Blue)?
-            && given_set.get(&Colour::Green)? >= max_seen.get(&Colour::Green)?;
-
-        Some(is_possible)
+    /// The fewest cubes of `colour` the bag could have held, which is 0 if it was never shown.
+    pub fn minimum_of(&self, colour: Colour) -> u32 {
+        self.revealed.iter().flatten().filter(|b| b.colour == colour).map(|b| b.count).max().unwrap_or(0)
     }
 
+    /// Whether every reveal fits in `given_set`.
+    pub fn is_possible(&self, given_set: &Bag) -> bool {
+        self.get_max_colours_seen()
+            .iter()
+            .all(|(colour, count)| given_set.count_of(*colour) >= *count)
+    }
 }
 
-struct BallData {
+#[derive(Debug)]
+pub(crate) struct BallData {
     colour: Colour,
     count: u32,
 }
@@ -122,23 +274,315 @@ impl BallData {
     }
 }
 
-impl From<&str> for BallData {
-    fn from(value: &str) -> Self {
-        let split = value.trim().split(" ").collect::<Vec<&str>>();
-        let colour = Colour::from(split[1]);
-        let count = str::parse(split[0]).unwrap_or(0);
+/// Where a game line stopped making sense, and what was expected there. Columns count bytes from 1.
+#[derive(Debug, PartialEq)]
+pub(crate) struct GameError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: &'static str,
+}
+
+impl GameError {
+    fn at(line: &str, error: nom::Err<VerboseError<&str>>) -> Self {
+        let errors = match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.errors,
+            nom::Err::Incomplete(_) => vec![],
+        };
+        let (rest, expected) = errors.iter()
+            .find_map(|(rest, kind)| match kind {
+                VerboseErrorKind::Context(expected) => Some((*rest, *expected)),
+                _ => None,
+            })
+            .unwrap_or(("", "a game"));
 
-        BallData::new(colour, count)
+        Self { line: 1, column: line.len() - rest.len() + 1, expected }
     }
 }
 
-fn is_game_valid_for_given_set(game_data: &GameData, given_set: &HashMap<Colour, u32>) -> Option<u32> {
-    let is_possible = game_data.is_possible(given_set)?;
+impl Display for GameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: expected {}", self.line, self.column, self.expected)
+    }
+}
+
+type ParseResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+/// A reveal's draws as `(count, colour)` pairs, before the colours are interned.
+type Reveal<'a> = Vec<(u32, &'a str)>;
+
+fn parse_game(i: &str) -> ParseResult<'_, (u32, Vec<Reveal<'_>>)> {
+    let (i, id) = context("`Game <id>`", preceded(pair(tag("Game"), space1), parse_count))(i)?;
+    let (i, _) = context("`:` after the game id", char(':'))(i)?;
+    let (i, reveals) = separated_list1(preceded(space0, char(';')), cut(parse_reveal))(i)?;
+    let (i, _) = context("`,`, `;` or the end of the line", preceded(space0, eof))(i)?;
+
+    Ok((i, (id, reveals)))
+}
+
+fn parse_reveal(i: &str) -> ParseResult<'_, Reveal<'_>> {
+    separated_list1(preceded(space0, char(',')), preceded(space0, cut(parse_draw)))(i)
+}
+
+fn parse_draw(i: &str) -> ParseResult<'_, (u32, &str)> {
+    context("a draw like `3 blue`", separated_pair(parse_count, space1, alpha1))(i)
+}
+
+fn parse_count(i: &str) -> ParseResult<'_, u32> {
+    map_res(digit1, str::parse)(i)
+}
+
+/// The bag a game most likely came from, treating each reveal as cubes drawn without replacement
+/// and put back before the next reveal.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Estimate {
+    pub cubes: Vec<(Colour, u32)>,
+    pub log_likelihood: f64,
+    /// The search reached the cube limit, so a bigger bag may well be more likely still.
+    pub at_limit: bool,
+}
+
+impl Estimate {
+    pub fn describe(&self, colours: &Colours) -> String {
+        self.cubes.iter().map(|(colour, count)| format!("{}={}", colours.name(*colour), count)).collect::<Vec<_>>().join(",")
+    }
+}
+
+/// How many cubes of `colour` a reveal drew, however many of its draws name it.
+pub(crate) fn drawn(reveal: &[BallData], colour: Colour) -> u32 {
+    reveal.iter().filter(|b| b.colour == colour).map(|b| b.count).sum()
+}
+
+/// How many cubes a reveal drew in all.
+pub(crate) fn reveal_size(reveal: &[BallData]) -> u32 {
+    reveal.iter().map(|b| b.count).sum()
+}
+
+/// The log of the chance of drawing every reveal of `game` from `bag`. Each reveal is a
+/// multivariate hypergeometric draw: the ways to pick its cubes of each colour over the ways to
+/// pick that many cubes from the whole bag. Negative infinity when the bag couldn't have done it.
+pub(crate) fn log_likelihood(game: &GameData, bag: &Bag) -> f64 {
+    let total = bag.colours().map(|c| bag.count_of(c) as u64).sum::<u64>();
+    game.revealed.iter()
+        .map(|reveal| {
+            let size = reveal_size(reveal) as u64;
+            if size > total {
+                return f64::NEG_INFINITY;
+            }
 
-    if is_possible { Some(game_data.id) } else { None }
+            let ways = reveal.iter()
+                .map(|b| b.colour)
+                .unique()
+                .map(|c| ln_binomial(bag.count_of(c) as u64, drawn(reveal, c) as u64))
+                .sum::<f64>();
+            ways - ln_binomial(total, size)
+        })
+        .sum()
 }
 
-fn get_power_of_minimum_required(game_data: &GameData) -> u32 {
-    let minimum_required = game_data.get_max_colours_seen();
-    minimum_required.iter().fold(1, |acc, (_, count)| acc * count)
+/// Finds the bag of the game's colours holding at most `max_cubes` cubes most likely to have produced
+/// its reveals, preferring the smallest among equally likely bags. Takes O(colours · max_cubes²).
+/// Colours the game never showed are left out, as any of them would only make it less likely.
+/// Returns `None` if even the smallest possible bag holds more than `max_cubes`.
+pub(crate) fn most_likely_bag(game: &GameData, max_cubes: u32) -> Option<Estimate> {
+    let colours = game.revealed.iter().flatten().map(|b| b.colour).sorted().dedup().collect::<Vec<Colour>>();
+    let minimum = colours.iter()
+        .map(|c| game.revealed.iter().map(|r| drawn(r, *c)).max().unwrap_or(0))
+        .collect::<Vec<u32>>();
+    if minimum.iter().sum::<u32>() > max_cubes {
+        return None;
+    }
+
+    // The likelihood splits into a term per colour and one for the bag's total, so each is
+    // tabulated once for every count up to the limit rather than per candidate bag.
+    let per_colour = colours.iter()
+        .map(|c| (0..=max_cubes as u64).map(|n| game.revealed.iter().map(|r| ln_binomial(n, drawn(r, *c) as u64)).sum()).collect())
+        .collect::<Vec<Vec<f64>>>();
+    let per_total = (0..=max_cubes as u64)
+        .map(|n| game.revealed.iter().map(|r| ln_binomial(n, reveal_size(r) as u64)).sum())
+        .collect::<Vec<f64>>();
+
+    // best[k][t] is the largest sum of colour terms over colours k onward holding exactly t cubes
+    // between them, so each colour only combines with a table of totals rather than every bag.
+    let limit = max_cubes as usize;
+    let mut best = vec![vec![f64::NEG_INFINITY; limit + 1]; colours.len() + 1];
+    best[colours.len()][0] = 0.0;
+    for k in (0..colours.len()).rev() {
+        for total in 0..=limit {
+            best[k][total] = (minimum[k] as usize..=total)
+                .map(|count| per_colour[k][count] + best[k + 1][total - count])
+                .fold(f64::NEG_INFINITY, f64::max);
+        }
+    }
+
+    let mut chosen: Option<(f64, usize)> = None;
+    for total in (0..=limit).filter(|t| best[0][*t] > f64::NEG_INFINITY) {
+        let log_likelihood = best[0][total] - per_total[total];
+        if chosen.is_none_or(|(most, _)| log_likelihood > most + TIE) {
+            chosen = Some((log_likelihood, total));
+        }
+    }
+    let (log_likelihood, total) = chosen?;
+
+    // Walk the table back, giving each colour the fewest cubes that still reach the best sum.
+    let mut remaining = total;
+    let counts = (0..colours.len()).map(|k| {
+        let count = (minimum[k] as usize..=remaining)
+            .find(|count| per_colour[k][*count] + best[k + 1][remaining - count] >= best[k][remaining] - TIE)
+            .expect("a count reaching the best sum");
+        remaining -= count;
+        count as u32
+    }).collect::<Vec<u32>>();
+
+    Some(Estimate {
+        at_limit: total == limit,
+        cubes: colours.into_iter().zip(counts).collect(),
+        log_likelihood,
+    })
+}
+
+/// How close two log-likelihoods must be to count as equally likely.
+const TIE: f64 = 1e-12;
+
+fn is_game_valid_for_given_set(game_data: &GameData, given_set: &Bag) -> Option<u32> {
+    if game_data.is_possible(given_set) { Some(game_data.id) } else { None }
+}
+
+pub(crate) fn get_power_of_minimum_required(game_data: &GameData, colours: &[Colour]) -> u32 {
+    colours.iter().fold(1, |acc, colour| acc * game_data.minimum_of(*colour))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_game_line() {
+        let mut colours = Colours::new();
+        let game = GameData::parse("Game 11: 3 blue, 4 red; 1 red, 2 green", &mut colours).unwrap();
+
+        assert_eq!(game.id, 11);
+        assert_eq!(game.revealed.len(), 2);
+        assert_eq!(game.get_max_colours_seen().get(&colours.intern("red")), Some(&4));
+    }
+
+    #[test]
+    fn should_reject_draw_without_colour() {
+        let error = |line: &str| GameData::parse(line, &mut Colours::new()).err().map(|e| (e.column, e.expected));
+        assert_eq!(error("Game 1: 3"), Some((9, "a draw like `3 blue`")));
+        assert_eq!(error("Game 1: 3 blue; 4"), Some((17, "a draw like `3 blue`")));
+        assert_eq!(error("Game 1: 3 blue,, 4 red"), Some((16, "a draw like `3 blue`")));
+        assert_eq!(error("Game 1: 99999999999 blue"), Some((9, "a draw like `3 blue`")));
+    }
+
+    #[test]
+    fn should_intern_colours_ignoring_case() {
+        let mut colours = Colours::new();
+        let teal = colours.intern("Teal");
+        assert_eq!(colours.intern("teal"), teal);
+        assert_ne!(colours.intern("red"), teal);
+        assert_eq!(colours.intern("TEAL"), teal);
+    }
+
+    #[test]
+    fn should_treat_unseen_colours_as_zero() {
+        let mut colours = Colours::new();
+        let game = GameData::parse("Game 3: 2 teal, 1 red; 5 teal", &mut colours).unwrap();
+        let (teal, red, blue) = (colours.intern("teal"), colours.intern("red"), colours.intern("blue"));
+
+        assert!(game.is_possible(&Bag::parse("teal=5,red=1", &mut colours).unwrap()));
+        assert!(!game.is_possible(&Bag::parse("teal=4,red=1,blue=9", &mut colours).unwrap()));
+        assert!(!game.is_possible(&Bag::parse("teal=5", &mut colours).unwrap()));
+        assert_eq!(game.minimum_of(blue), 0);
+        assert_eq!(get_power_of_minimum_required(&game, &[teal, red]), 5);
+        assert_eq!(get_power_of_minimum_required(&game, &[teal, red, blue]), 0);
+    }
+
+    #[test]
+    fn should_sum_possible_games_per_bag() {
+        let input = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+        let mut colours = Colours::new();
+        let puzzle = Bag::parse(PUZZLE_BAG, &mut colours).unwrap();
+        let roomy = Bag::parse("red = 20, green = 13, blue = 15", &mut colours).unwrap();
+        let games = parse_games(input, &mut colours).unwrap();
+
+        assert_eq!(possible_games(&games, &puzzle), (3, 8));
+        assert_eq!(possible_games(&games, &roomy), (5, 15));
+        assert_eq!(part1(input), 8);
+    }
+
+    #[test]
+    fn should_reject_malformed_bags() {
+        assert!(matches!(Bag::parse("red=12,green", &mut Colours::new()), Err(BagError::Malformed(e)) if e == "green"));
+        assert!(matches!(Bag::parse("red=x", &mut Colours::new()), Err(BagError::Malformed(_))));
+        assert!(matches!(Bag::parse("=3", &mut Colours::new()), Err(BagError::Malformed(_))));
+    }
+
+    #[test]
+    fn should_report_where_games_go_wrong() {
+        let error = |line: &str| GameData::parse(line, &mut Colours::new()).err().map(|e| (e.column, e.expected));
+        assert_eq!(error("Gme 1: 3 blue"), Some((1, "`Game <id>`")));
+        assert_eq!(error("Game 1 3 blue"), Some((7, "`:` after the game id")));
+        assert_eq!(error("Game 1: 3 blue 4 red"), Some((15, "`,`, `;` or the end of the line")));
+
+        let error = parse_games("Game 1: 3 blue\nGame 2: red", &mut Colours::new()).unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 9: expected a draw like `3 blue`");
+    }
+
+    #[test]
+    fn should_keep_draws_per_reveal() {
+        let game = GameData::parse("Game 7: 1 red , 2 green;3 blue ", &mut Colours::new()).unwrap();
+        assert_eq!(game.revealed.iter().map(|r| r.iter().map(|b| b.count).collect()).collect::<Vec<Vec<u32>>>(), vec![vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn should_find_likelihood_of_bag() {
+        let mut colours = Colours::new();
+        let game = GameData::parse("Game 1: 2 red", &mut colours).unwrap();
+
+        let likelihood = log_likelihood(&game, &Bag::parse("red=2,blue=2", &mut colours).unwrap()).exp();
+        assert!((likelihood - 1.0 / 6.0).abs() < 1e-12);
+        assert_eq!(log_likelihood(&game, &Bag::parse("red=1,blue=9", &mut colours).unwrap()), f64::NEG_INFINITY);
+        assert_eq!(log_likelihood(&game, &Bag::parse("blue=9", &mut colours).unwrap()), f64::NEG_INFINITY);
+        assert_eq!(log_likelihood(&game, &Bag::parse("red=1", &mut colours).unwrap()), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn should_estimate_most_likely_bag() {
+        let mut colours = Colours::new();
+        let (red, blue) = (colours.intern("red"), colours.intern("blue"));
+
+        let single = most_likely_bag(&GameData::parse("Game 1: 2 red, 1 blue", &mut colours).unwrap(), 20).unwrap();
+        assert_eq!((single.cubes, single.log_likelihood, single.at_limit), (vec![(red, 2), (blue, 1)], 0.0, false));
+
+        let alternating = most_likely_bag(&GameData::parse("Game 2: 1 red; 1 blue", &mut colours).unwrap(), 20).unwrap();
+        assert_eq!(alternating.cubes, vec![(red, 1), (blue, 1)]);
+        assert!((alternating.log_likelihood - 0.25_f64.ln()).abs() < 1e-12);
+        assert_eq!(alternating.describe(&colours), "red=1,blue=1");
+    }
+
+    #[test]
+    fn should_estimate_bags_of_many_colours() {
+        let mut colours = Colours::new();
+        let names = ["red", "green", "blue", "cyan", "magenta", "yellow", "black", "white", "grey", "brown"];
+        let game = GameData::parse(&format!("Game 1: {}; 3 red", names.iter().map(|n| format!("1 {}", n)).join(", ")), &mut colours).unwrap();
+
+        let estimate = most_likely_bag(&game, 200).unwrap();
+        assert_eq!(estimate.describe(&colours), "red=3,green=1,blue=1,cyan=1,magenta=1,yellow=1,black=1,white=1,grey=1,brown=1");
+        assert!(!estimate.at_limit);
+    }
+
+    #[test]
+    fn estimate_should_flag_bags_that_keep_growing() {
+        let mut colours = Colours::new();
+        let game = GameData::parse("Game 1: 3 red; 3 blue", &mut colours).unwrap();
+
+        let estimate = most_likely_bag(&game, 12).unwrap();
+        assert_eq!(estimate.describe(&colours), "red=6,blue=6");
+        assert!(estimate.at_limit);
+        assert!(most_likely_bag(&game, 5).is_none());
+    }
 }
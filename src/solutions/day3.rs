@@ -1,163 +1,93 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-const LINE_LENGTH: usize = 140;
-
-pub(crate) fn day3(path: String) {
-    let file = File::open(path).unwrap();
-    let buffer = BufReader::new(file);
-    let lines = buffer.lines().map(|l| l.unwrap()).collect::<Vec<String>>();
-    let char_list = lines.iter().map(|l| l.chars().collect::<Vec<char>>()).collect::<Vec<Vec<char>>>();
-    let total = parse_engine_schematic(&char_list);
-
-    println!("Total of found parts: {}", total);
-
-    let total_gear_ratio = parse_engine_schematic_for_gears(&char_list);
-    println!("Total of gear ratios: {}", total_gear_ratio);
+use std::num::ParseIntError;
+use itertools::Itertools;
+use crate::utilities::grid::{Grid, Run};
+
+pub(crate) fn part1(i: &str) -> u32 {
+    numbers_around_symbols(&parse_schematic(i).unwrap())
+        .unwrap()
+        .iter()
+        .flatten()
+        .sum()
 }
 
-fn parse_engine_schematic(lines: &Vec<Vec<char>>) -> u32 {
-    let mut total = 0;
-    for (r, l) in lines.iter().enumerate() {
-        let symbol_idxs = l
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| *c != &'.' && !c.is_digit(10))
-            .collect::<Vec<(usize, &char)>>();
-
-        let mut found_numbers: Vec<u32> = vec![];
-        for (i, _) in symbol_idxs {
-            let mut found_for_symbol = get_surrounding_numbers(lines, i, r);
-
-            found_numbers.append( &mut found_for_symbol);
-        }
-
-        total += found_numbers.iter().fold(0, |acc, n| acc + n);
-    }
-
-    total
+pub(crate) fn part2(i: &str) -> u32 {
+    numbers_around_symbols(&parse_schematic(i).unwrap())
+        .unwrap()
+        .iter()
+        .filter(|numbers| numbers.len() == 2)
+        .map(|numbers| numbers[0] * numbers[1])
+        .sum()
 }
 
-fn parse_engine_schematic_for_gears(lines: &Vec<Vec<char>>) -> u32 {
-    let mut total = 0;
-    for (r, l) in lines.iter().enumerate() {
-        let symbol_idxs = l
-            .iter()
-            .enumerate()
-            .filter(|(_, c)| *c != &'.' && !c.is_digit(10))
-            .collect::<Vec<(usize, &char)>>();
-
-        let mut found_numbers: Vec<u32> = vec![];
-        for (i, _) in symbol_idxs {
-            let mut found_for_symbol = get_surrounding_numbers(lines, i, r);
-
-            if found_for_symbol.len() == 2 {
-                total += found_for_symbol[0] * found_for_symbol[1];
-            }
-        }
-
-        // total += found_numbers.iter().fold(0, |acc, n| acc + n);
-    }
-
-    total
+/// Reads the schematic into a grid, failing unless every row is the same width.
+pub(crate) fn parse_schematic(i: &str) -> Option<Grid<char>> {
+    Grid::parse_chars(i)
 }
 
-fn get_surrounding_numbers(lines: &Vec<Vec<char>>, col: usize, row: usize) -> Vec<u32> {
-    let mut found_numbers = vec![];
-    let row_count = lines.len();
-
-    let l = lines[row][col_idx(col.saturating_sub(1))];
-    if l.is_digit(10) {
-        found_numbers.push(get_number(lines, row, col_idx(col - 1), Direction::Backwards));
-    }
-
-    let r = lines[row][col_idx(col.saturating_add(1))];
-    if r.is_digit(10) {
-        found_numbers.push(get_number(lines, row, col_idx(col + 1), Direction::Forward));
-    }
-
-    let prev_row = row.saturating_sub(1);
-    if prev_row < row {
-        get_values_from_target_row(lines, col, prev_row, &mut found_numbers);
-    }
-
-    let next_row = row.saturating_add(1);
-    if next_row <= row_count {
-        get_values_from_target_row(lines, col, next_row, &mut found_numbers);
-    }
-
-    found_numbers
+/// Every number found around each symbol of the schematic, grouped by symbol.
+/// Fails when a run of digits is too long to fit a `u32`.
+pub(crate) fn numbers_around_symbols(schematic: &Grid<char>) -> Result<Vec<Vec<u32>>, ParseIntError> {
+    schematic.iter()
+        .filter(|(_, c)| is_symbol(c))
+        .map(|((row, col), _)| get_surrounding_numbers(schematic, row, col))
+        .collect()
 }
 
-fn get_values_from_target_row(lines: &Vec<Vec<char>>, col: usize, row: usize, found_numbers: &mut Vec<u32>) {
-    let l = lines[row][col_idx(col.saturating_sub(1))];
-    let m = lines[row][col_idx(col)];
-    let r = lines[row][col_idx(col.saturating_add(1))];
-
-    if !m.is_digit(10) {
-        if l.is_digit(10) {
-            found_numbers.push(get_number(lines, row, col_idx(col - 1), Direction::Backwards));
-        }
-
-        if r.is_digit(10) {
-            found_numbers.push(get_number(lines, row, col_idx(col + 1), Direction::Forward));
-        }
-    } else if m.is_digit(10) && (r.is_digit(10) || l.is_digit(10)) {
-        found_numbers.push(backtracking_get_number(lines, row, col_idx(col)));
-    } else {
-        found_numbers.push(m.to_digit(10).unwrap());
-    }
+fn is_symbol(c: &char) -> bool {
+    *c != '.' && !c.is_ascii_digit()
 }
 
-fn backtracking_get_number(lines: &Vec<Vec<char>>, row: usize, col: usize) -> u32 {
-    let mut curr_col = col;
-    let mut curr = lines[row][col];
-    while curr.is_digit(10) {
-        curr_col = curr_col.saturating_sub(1);
-        curr = lines[row][curr_col];
-    }
-
-    let num = get_number(lines, row, curr_col.saturating_add(1), Direction::Forward);
-
-    num
+/// Numbers touching the cell at `(row, col)`, each counted once however many of its digits touch.
+fn get_surrounding_numbers(schematic: &Grid<char>, row: usize, col: usize) -> Result<Vec<u32>, ParseIntError> {
+    schematic.neighbours8(row, col)
+        .filter_map(|(r, c)| schematic.run_at(r, c, char::is_ascii_digit))
+        .unique()
+        .map(|run| number_in(schematic, run))
+        .collect()
 }
 
-fn get_number(lines: &Vec<Vec<char>>, row: usize, col: usize, direction: Direction) -> u32 {
-    let mut number_str = String::new();
-    let mut curr = lines[row][col];
-    let mut col = col;
-    while curr.is_digit(10) {
-        number_str.push(curr);
-
-        let next_col = col_idx(match direction {
-            Direction::Forward => col.saturating_add(1),
-            Direction::Backwards => col.saturating_sub(1)
-        });
+fn number_in(schematic: &Grid<char>, run: Run) -> Result<u32, ParseIntError> {
+    schematic.cells_in(run).iter().collect::<String>().parse::<u32>()
+}
 
-        if col == 0 {
-            break;
-        }
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = r#"467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598.."#;
+
+    #[test]
+    fn should_sum_part_numbers() {
+        assert_eq!(part1(EXAMPLE), 4361);
+        assert_eq!(part2(EXAMPLE), 467835);
+    }
 
-        if next_col == LINE_LENGTH {
-            break;
-        }
+    #[test]
+    fn should_find_numbers_touching_the_edges() {
+        let schematic = parse_schematic("12.\n..*\n.34").unwrap();
+        assert_eq!(numbers_around_symbols(&schematic), Ok(vec![vec![12, 34]]));
 
-        col = next_col;
-        curr = lines[row][col];
+        let schematic = parse_schematic("*5\n7.").unwrap();
+        assert_eq!(numbers_around_symbols(&schematic), Ok(vec![vec![5, 7]]));
     }
 
-    if direction == Direction::Backwards {
-        number_str = number_str.chars().rev().collect::<String>();
+    #[test]
+    fn should_reject_numbers_too_long_for_u32() {
+        let schematic = parse_schematic("12345678901*").unwrap();
+        assert!(numbers_around_symbols(&schematic).is_err());
     }
-    number_str.parse::<u32>().unwrap_or(0)
-}
-
-#[derive(PartialEq)]
-enum Direction {
-    Forward,
-    Backwards
-}
 
-fn col_idx(idx: usize) -> usize {
-    if idx > LINE_LENGTH { LINE_LENGTH } else { idx }
+    #[test]
+    fn should_reject_ragged_schematic() {
+        assert!(parse_schematic("..\n...").is_none());
+    }
 }
@@ -1,62 +1,59 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::VecDeque;
+use nom::bytes::complete::tag;
+use nom::character::complete::space0;
+use nom::combinator::all_consuming;
+use nom::IResult;
+use nom::sequence::{preceded, terminated, tuple};
+use crate::utilities::parsing::{labelled, number, numbers};
 
-pub(crate) fn day4(path: String) {
-    let file = File::open(path).unwrap();
-    let buffer = BufReader::new(file);
-    let lines = buffer.lines().map(|l| l.unwrap()).collect::<Vec<String>>();
-
-    let split_lines = lines
-        .iter()
-        .map(|l| l.split(&[':', '|']).collect::<Vec<&str>>())
-
-        .collect::<Vec<Vec<&str>>>();
-    let winners_for_card = split_lines
-        .iter()
-        .map(|l| get_winning_numbers(l))
-        .collect::<Vec<Vec<u32>>>();
+pub(crate) fn part1(i: &str) -> u32 {
+    winners_for_cards(i)
+        .map(|winners| calculate_points_for_card(&winners))
+        .sum()
+}
 
-    let total_points = winners_for_card
-        .iter()
-        .fold(0, |acc, winners| acc + calculate_points_for_card(winners));
+/// Copies only ever go to the next few cards, so only the copies still owed to those are kept,
+/// rather than a count for every card.
+pub(crate) fn part2(i: &str) -> u64 {
+    let mut owed_copies: VecDeque<u64> = VecDeque::new();
+    let mut total = 0;
+    for winners in winners_for_cards(i) {
+        let count = add_cards(1, owed_copies.pop_front().unwrap_or(0));
+        total = add_cards(total, count);
 
-    let mut card_count = vec![0_u32; winners_for_card.len()];
-    for (idx, winners) in winners_for_card.iter().enumerate() {
-        card_count[idx] += 1;
-        let is_winner = winners.len() > 0;
-        if is_winner {
-            for _count in 0..card_count[idx] {
-                for n in 0..winners.len() {
-                    card_count[idx + n + 1] += 1;
-                }
-            }
+        if owed_copies.len() < winners.len() {
+            owed_copies.resize(winners.len(), 0);
         }
+        owed_copies.iter_mut().take(winners.len()).for_each(|copies| *copies = add_cards(*copies, count));
     }
 
-    let total_card_count = card_count.into_iter().fold(0, |acc, n| acc + n);
-    println!("Total Points: {}", total_points);
-    println!("Total Count of Cards: {}", total_card_count);
+    total
 }
 
-fn get_winning_numbers(split_line: &Vec<&str>) -> Vec<u32> {
-    let winning = split_line[1]
-        .trim()
-        .split(" ")
-        .filter(|x| !x.trim().is_empty())
-        .map(|x| x.parse::<u32>().unwrap())
-        .collect::<Vec<u32>>();
+/// Copies can grow exponentially down the pile, so running out of room is an error rather than a wrap.
+fn add_cards(a: u64, b: u64) -> u64 {
+    a.checked_add(b).expect("more scratchcards than fit in a u64")
+}
 
-    let mine = split_line[2]
-        .trim()
-        .split(" ")
-        .filter(|x| !x.trim().is_empty())
-        .map(|x| x.parse::<u32>().unwrap().to_owned())
-        .collect::<Vec<u32>>();
+fn winners_for_cards(i: &str) -> impl Iterator<Item = Vec<u32>> + '_ {
+    i.lines().map(|l| get_winning_numbers(l).unwrap())
+}
+
+/// The numbers on a card that are also among its winning numbers, failing if either list is
+/// missing or holds something other than numbers.
+pub(crate) fn get_winning_numbers(line: &str) -> Option<Vec<u32>> {
+    let (_, (winning, mine)) = parse_card(line).ok()?;
+    Some(mine.into_iter().filter(|x| winning.contains(x)).collect())
+}
 
-    mine.into_iter().filter(|x| winning.contains(x)).collect()
+fn parse_card(i: &str) -> IResult<&str, (Vec<u32>, Vec<u32>)> {
+    all_consuming(tuple((
+        preceded(tuple((labelled("Card", number::<u32>), tag(":"))), numbers),
+        preceded(tuple((space0, tag("|"))), terminated(numbers, space0))
+    )))(i)
 }
 
-fn calculate_points_for_card(winners: &Vec<u32>) -> u32 {
+fn calculate_points_for_card(winners: &[u32]) -> u32 {
     match winners.len() {
         0 => 0,
         1 => 1,
@@ -65,4 +62,21 @@ fn calculate_points_for_card(winners: &Vec<u32>) -> u32 {
             2_u32.pow(exp as u32)
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_find_winning_numbers() {
+        let line = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53";
+        assert_eq!(get_winning_numbers(line), Some(vec![83, 86, 17, 48]));
+    }
+
+    #[test]
+    fn should_reject_malformed_cards() {
+        assert_eq!(get_winning_numbers("Card 1: 41 48 "), None);
+        assert_eq!(get_winning_numbers("Card 1: 41 x | 41 "), None);
+        assert_eq!(get_winning_numbers("Card 1: 41 | 41 x"), None);
+    }
+}
@@ -1,16 +1,15 @@
-use std::fs::File;
-use std::io::{BufReader, Read};
 use itertools::Itertools;
-use nom;
 use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, multispace1};
-use nom::character::complete::u64 as nom_u64;
+use nom::character::complete::{alpha1, line_ending, space0, space1};
+use nom::combinator::map_res;
 use nom::IResult;
-use nom::multi::separated_list1;
-use nom::sequence::tuple;
+use nom::multi::many1;
+use nom::sequence::{pair, separated_pair, terminated, tuple};
+use crate::utilities::interval::{Interval, OffsetMap, RangeSet};
+use crate::utilities::parsing::{blocks, labelled, lines, number, numbers};
 
 #[derive(Debug, PartialEq)]
-enum Category {
+pub(crate) enum Category {
     Seed,
     Soil,
     Fertilizer,
@@ -21,138 +20,130 @@ enum Category {
     Location
 }
 
-impl From<&str> for Category {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for Category {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value.to_lowercase().as_str() {
-            "seed" => Self::Seed,
-            "soil" => Self::Soil,
-            "fertilizer" => Self::Fertilizer,
-            "water" => Self::Water,
-            "light" => Self::Light,
-            "temperature" => Self::Temperature,
-            "humidity" => Self::Humidity,
-            "location" => Self::Location,
-            _ => unimplemented!("no other categories supported"),
+            "seed" => Ok(Self::Seed),
+            "soil" => Ok(Self::Soil),
+            "fertilizer" => Ok(Self::Fertilizer),
+            "water" => Ok(Self::Water),
+            "light" => Ok(Self::Light),
+            "temperature" => Ok(Self::Temperature),
+            "humidity" => Ok(Self::Humidity),
+            "location" => Ok(Self::Location),
+            other => Err(format!("unsupported category {}", other)),
         }
     }
 }
 
 #[derive(Debug, PartialEq)]
-struct CategoryMap {
+pub(crate) struct CategoryMap {
     source: Category,
     destination: Category,
-    conversion_ranges: Vec<(u64, u64, u64)>
+    conversion: OffsetMap
 }
 
 impl CategoryMap {
+    /// Takes the almanac's `(destination, source, length)` rows.
     pub fn new(source: Category, destination: Category, conversion_ranges: Vec<(u64, u64, u64)>) -> Self {
         Self {
             source,
             destination,
-            conversion_ranges
+            conversion: conversion_ranges.into_iter().map(|(d, s, l)| (Interval::with_length(s, l), d)).collect()
         }
     }
 
-    pub fn output_for_range(&self, source: u64, range: (u64, u64, u64)) -> Option<u64> {
-        if source >= range.1 && source < (range.1+range.2) {
-            return Some(range.0 + source - range.1)
-        }
-        None
+    pub fn convert(&self, source: u64) -> u64 {
+        self.conversion.apply(source)
     }
 
-    pub fn convert(&self, source: u64) -> u64 {
-        for map_ranges in self.conversion_ranges.iter() {
-            match self.output_for_range(source, *map_ranges) {
-                None => {}
-                Some(n) => { return n }
-            }
-        }
-        source
+    pub fn convert_ranges(&self, ranges: &RangeSet) -> RangeSet {
+        self.conversion.apply_set(ranges)
     }
 }
 
-pub fn day5(path: String) {
-    let file = File::open(path).unwrap();
-    let mut buffer = BufReader::new(file);
-    let mut i = String::new();
-    _ = buffer.read_to_string(&mut i);
+pub(crate) fn part1(i: &str) -> u64 {
+    let (_, (seed_list, category_maps)) = parse_full_conversion_map_file(i).unwrap();
+    lowest_location_for_seeds(&seed_list, &category_maps)
+}
 
-    let (_, (seed_list, category_maps)) = parse_full_conversion_map_file(&i).unwrap();
+pub(crate) fn part2(i: &str) -> u64 {
+    let (_, (seed_list, category_maps)) = parse_full_conversion_map_file(i).unwrap();
+    let mut ranges = RangeSet::new(seed_list.into_iter().tuples().map(|(start, count)| Interval::with_length(start, count)));
+    for map in category_maps.iter() {
+        ranges = map.convert_ranges(&ranges);
+    }
+
+    ranges.min().unwrap_or(0)
+}
 
-    part1(&seed_list, &category_maps);
-    part2(seed_list, category_maps);
+pub(crate) fn part2_brute_force(i: &str) -> u64 {
+    let (_, (seed_list, category_maps)) = parse_full_conversion_map_file(i).unwrap();
+    lowest_location_for_seed_ranges(seed_list, category_maps)
 }
 
-fn part2(seed_list: Vec<u64>, category_maps: Vec<CategoryMap>) {
-    let mut lowest = 0;
+fn lowest_location_for_seed_ranges(seed_list: Vec<u64>, category_maps: Vec<CategoryMap>) -> u64 {
+    let mut lowest = None;
     let range_pairs = seed_list.into_iter().tuples().collect::<Vec<(u64, u64)>>();
-    println!("total seed pairs: {}", range_pairs.len() / 2);
     for (start, count) in range_pairs.into_iter() {
-        println!("Pair ({}, {}) starting...", &start, &count);
         for seed in start..(start + count) {
             let mut result = seed;
             for map in category_maps.iter() {
                 result = map.convert(result);
             }
-            if lowest == 0 || result < lowest {
-                println!("New lowest found: {}", result);
-                lowest = result;
+            if lowest.is_none_or(|l| result < l) {
+                lowest = Some(result);
             }
 
         }
-        println!("..finished");
     }
 
-    println!("lowest location {}", lowest);
+    lowest.unwrap_or(0)
 }
 
-fn part1(seed_list: &Vec<u64>, category_maps: &Vec<CategoryMap>) {
+fn lowest_location_for_seeds(seed_list: &[u64], category_maps: &[CategoryMap]) -> u64 {
     let mut locations = vec![];
     for seed in seed_list.iter() {
-        let mut result = seed.clone();
+        let mut result = *seed;
         for map in category_maps.iter() {
             result = map.convert(result);
         }
         locations.push(result);
     }
 
-    locations.sort_by(|a, b| a.cmp(b));
-    println!("Lowest location: {}", locations[0]);
+    locations.sort();
+    locations[0]
 }
 
 fn parse_seeds(i: &str) -> IResult<&str, Vec<u64>> {
-    tuple((tag("seeds: "), separated_list1(tag(" "), nom_u64), tag("\n")))(i)
-        .map(|(i, (_, s, _))| (i, s))
+    terminated(labelled("seeds:", numbers), line_ending)(i)
+}
+
+fn parse_category(i: &str) -> IResult<&str, Category> {
+    map_res(alpha1, Category::try_from)(i)
 }
 
 fn parse_map_categories(i: &str) -> IResult<&str, (Category, Category)>{
-    tuple((alpha1, tag("-to-"), alpha1, tag(" map:\n")))(i)
-        .map(|(i, (s, _, d, _))| (i, (s.into(), d.into())))
+    tuple((parse_category, tag("-to-"), parse_category, tag(" map:"), line_ending))(i)
+        .map(|(i, (s, _, d, _, _))| (i, (s, d)))
 }
 
 fn parse_conversion_tuple(i: &str) -> IResult<&str, (u64, u64, u64)> {
-        tuple((nom_u64, tag(" "), nom_u64, tag(" "), nom_u64))(i)
-            .map(|(i, (d1, _, d2, _, d3))| (i, (d1, d2, d3)))
+    tuple((number, space1, number, space1, number))(i)
+        .map(|(i, (d1, _, d2, _, d3))| (i, (d1, d2, d3)))
 }
 
 fn parse_map(i: &str) -> IResult<&str, CategoryMap> {
     let (i, (source, destination)) = parse_map_categories(i)?;
-    let (i, conversion_ranges) = separated_list1(tag("\n"), parse_conversion_tuple)(i)?;
+    let (i, conversion_ranges) = lines(parse_conversion_tuple)(i)?;
     Ok((i, CategoryMap::new(source, destination, conversion_ranges)))
 }
 
-fn parse_maps(i: &str) -> IResult<&str, Vec<CategoryMap>>{
-    separated_list1(multispace1, parse_map)(i)
-}
-
-fn parse_full_conversion_map_file(i: &str) -> IResult<&str, (Vec<u64>, Vec<CategoryMap>)> {
-    let (i, (seeds, _, category_maps)) = tuple ((
-        parse_seeds,
-        multispace1,
-        parse_maps
-    ))(i)?;
-
-    Ok((i, (seeds, category_maps)))
+pub(crate) fn parse_full_conversion_map_file(i: &str) -> IResult<&str, (Vec<u64>, Vec<CategoryMap>)> {
+    // The seed line takes its own line break, so only the empty lines after it are left to skip.
+    separated_pair(parse_seeds, many1(pair(space0, line_ending)), blocks(parse_map))(i)
 }
 
 #[cfg(test)]
@@ -166,6 +157,11 @@ mod test {
         assert_eq!(parse_map_categories("water-to-fertilizer map:\n"), Ok(("", (Category::Water, Category::Fertilizer))));
     }
 
+    #[test]
+    fn should_reject_unknown_category() {
+        assert!(parse_map_categories("seed-to-fuel map:\n").is_err());
+    }
+
     #[test]
     fn should_extract_conversion_tuple() {
         assert_eq!(parse_conversion_tuple("88 18 7"), Ok(("", (88, 18, 7))));
@@ -182,11 +178,7 @@ mod test {
     fn should_extract_category_map() {
         let map_input = "seed-to-soil map:\n34344 43242342 5435345\n123 123 123\n4043589752 1302126249 251377544";
         assert_eq!(parse_map(map_input),
-                   Ok(("", CategoryMap {
-                       source: Category::Seed,
-                       destination: Category::Soil,
-                       conversion_ranges: vec![(34344, 43242342, 5435345), (123, 123, 123), (4043589752, 1302126249, 251377544)]
-                   })));
+                   Ok(("", CategoryMap::new(Category::Seed, Category::Soil, vec![(34344, 43242342, 5435345), (123, 123, 123), (4043589752, 1302126249, 251377544)]))));
     }
 
     #[test]
@@ -206,29 +198,17 @@ soil-to-fertilizer map:
                    Ok(("",
                        (vec![79, 14, 55, 13],
                        vec![
-                           CategoryMap {
-                               source: Category::Seed,
-                               destination: Category::Soil,
-                               conversion_ranges: vec![(50, 98, 2),(52, 50, 48)]
-                           },
-                           CategoryMap {
-                               source: Category::Soil,
-                               destination: Category::Fertilizer,
-                               conversion_ranges: vec![(0, 15,37), (37, 52, 2), (39, 0, 15)]
-                           }
+                           CategoryMap::new(Category::Seed, Category::Soil, vec![(50, 98, 2),(52, 50, 48)]),
+                           CategoryMap::new(Category::Soil, Category::Fertilizer, vec![(0, 15,37), (37, 52, 2), (39, 0, 15)])
                        ]))));
     }
 
     #[test]
     fn should_convert_to_destination() {
-        let map = CategoryMap {
-            source: Category::Seed,
-            destination: Category::Soil,
-            conversion_ranges: vec![
-                (50, 98, 2),
-                (52, 50, 48)
-            ]
-        };
+        let map = CategoryMap::new(Category::Seed, Category::Soil, vec![
+            (50, 98, 2),
+            (52, 50, 48)
+        ]);
 
         assert_eq!(map.convert(98), 50);
         assert_eq!(map.convert(55), 57);
@@ -236,4 +216,55 @@ soil-to-fertilizer map:
         assert_eq!(map.convert(10), 10);
     }
 
+    #[test]
+    fn should_convert_whole_ranges_to_destination() {
+        let map = CategoryMap::new(Category::Seed, Category::Soil, vec![
+            (50, 98, 2),
+            (52, 50, 48)
+        ]);
+
+        let converted = map.convert_ranges(&RangeSet::new([Interval::new(45, 55), Interval::new(97, 102)]));
+        assert_eq!(converted.intervals(), &[Interval::new(45, 57), Interval::new(99, 102)]);
+    }
+
+    #[test]
+    fn range_part2_should_match_brute_force() {
+        let input = r#"seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4"#;
+
+        assert_eq!(part2(input), 46);
+        assert_eq!(part2_brute_force(input), 46);
+    }
+
 }
\ No newline at end of file
@@ -1,35 +1,66 @@
-use std::fs::File;
-use std::io::{BufReader, Read};
-use nom::bytes::complete::tag;
-use nom::character::complete::{digit1, newline, space1, u32};
+use nom::character::complete::line_ending;
 use nom::IResult;
-use nom::multi::separated_list1;
-use nom::sequence::tuple;
+use nom::sequence::separated_pair;
+use crate::utilities::parsing::{labelled, numbers, spaced_digits};
 
-pub fn day6(path: String) {
-    let file = File::open(path).unwrap();
-    let mut buffer = BufReader::new(file);
-    let mut i = String::new();
-    _ = buffer.read_to_string(&mut i);
-
-    let (_, race_data) = parse_race_data(&i).unwrap();
-    let win_variants = race_data.iter().fold(1, |acc, r| acc * r.combinations_to_beat_record());
-
-    println!("Win variants: {}", win_variants);
+pub(crate) fn part1(i: &str) -> u32 {
+    let (_, race_data) = parse_race_data(i).unwrap();
+    race_data.iter().fold(1, |acc, r| acc * r.combinations_to_beat_record())
+}
 
-    let (_, (time, record)) = parse_race_data_combined(&i).unwrap();
-    let combos_pt2 =  (0..time).collect::<Vec<u64>>().iter().fold(0, |acc, x| {
+pub(crate) fn part2(i: &str) -> u64 {
+    let (_, (time, record)) = parse_race_data_combined(i).unwrap();
+    (0..time).collect::<Vec<u64>>().iter().fold(0, |acc, x| {
         let distance_travelled = (time - x) * x;
         if distance_travelled >= record {
             return acc + 1
         }
-        return acc
-    });
+        acc
+    })
+}
+
+pub(crate) fn part1_closed_form(i: &str) -> u64 {
+    let (_, race_data) = parse_race_data(i).unwrap();
+    race_data.iter().fold(1, |acc, r| acc * ways_to_reach_record(r.time as u64, r.record as u64))
+}
+
+pub(crate) fn part2_closed_form(i: &str) -> u64 {
+    let (_, (time, record)) = parse_race_data_combined(i).unwrap();
+    ways_to_reach_record(time, record)
+}
+
+/// Counts the hold times `h` in `0..time` travelling at least `record`, without trying each one.
+/// `h * (time - h)` is symmetric about `time / 2`, so only the shortest qualifying hold needs finding.
+fn ways_to_reach_record(time: u64, record: u64) -> u64 {
+    if record == 0 {
+        return time;
+    }
+
+    let Some(discriminant) = (time * time).checked_sub(4 * record) else { return 0 };
+    let mut shortest = (time - integer_sqrt(discriminant)) / 2;
+    while shortest * (time - shortest) < record {
+        shortest += 1;
+    }
+    while shortest > 0 && (shortest - 1) * (time - shortest + 1) >= record {
+        shortest -= 1;
+    }
 
-    println!("Win variants pt2: {}", combos_pt2);
+    time - 2 * shortest + 1
 }
 
-struct Race {
+fn integer_sqrt(n: u64) -> u64 {
+    let mut root = (n as f64).sqrt() as u64;
+    while root * root > n {
+        root -= 1;
+    }
+    while (root + 1) * (root + 1) <= n {
+        root += 1;
+    }
+
+    root
+}
+
+pub(crate) struct Race {
     time: u32,
     record: u32
 }
@@ -48,30 +79,44 @@ impl Race {
             if distance_travelled >= self.record {
                 return acc + 1
             }
-            return acc
+            acc
         })
     }
 }
 
-fn parse_race_data(i: &str) -> IResult<&str, Vec<Race>> {
-    let (i, times) = tuple((tag("Time:"), space1, separated_list1(space1, u32), newline))(i)
-        .map(|(i, (_, _, times, _))| (i, times))?;
-
-    let (i, records) = tuple((tag("Distance:"), space1, separated_list1(space1, u32)))(i)
-        .map(|(i, (_, _, records))| (i, records))?;
+pub(crate) fn parse_race_data(i: &str) -> IResult<&str, Vec<Race>> {
+    let (i, (times, records)) = separated_pair(
+        labelled("Time:", numbers::<u32>),
+        line_ending,
+        labelled("Distance:", numbers::<u32>)
+    )(i)?;
 
     Ok((i, times.into_iter().zip(records).map(|(t, r)| Race::new(t, r)).collect()))
 }
 
-fn parse_race_data_combined(i: &str) -> IResult<&str, (u64, u64)> {
-    let (i, times) = tuple((tag("Time:"), space1, separated_list1(space1, digit1), newline))(i)
-        .map(|(i, (_, _, times, _))| (i, times))?;
+pub(crate) fn parse_race_data_combined(i: &str) -> IResult<&str, (u64, u64)> {
+    separated_pair(labelled("Time:", spaced_digits), line_ending, labelled("Distance:", spaced_digits))(i)
+}
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    let (i, records) = tuple((tag("Distance:"), space1, separated_list1(space1, digit1)))(i)
-        .map(|(i, (_, _, records))| (i, records))?;
+    const EXAMPLE: &str = "Time:      7  15   30\nDistance:  9  40  200";
 
-    let time = times.iter().fold(String::new(), |acc, x| format!("{}{}", acc, x)).parse::<u64>().unwrap();
-    let distance = records.iter().fold(String::new(), |acc, x| format!("{}{}", acc, x)).parse::<u64>().unwrap();
+    #[test]
+    fn combined_race_should_reject_overflowing_numbers() {
+        assert!(parse_race_data_combined("Time: 99999999999 99999999999\nDistance: 1").is_err());
+    }
 
-    Ok((i, (time, distance)))
-}
\ No newline at end of file
+    #[test]
+    fn closed_form_should_match_loop() {
+        assert_eq!(part1(EXAMPLE) as u64, part1_closed_form(EXAMPLE));
+        assert_eq!(part2(EXAMPLE), part2_closed_form(EXAMPLE));
+        for time in 0..40 {
+            for record in 0..400 {
+                let looped = (0..time).filter(|h| h * (time - h) >= record).count() as u64;
+                assert_eq!(ways_to_reach_record(time, record), looped, "time {} record {}", time, record);
+            }
+        }
+    }
+}
@@ -1,43 +1,54 @@
 use std::cmp::Ordering;
 use std::collections::{BTreeMap};
-use std::fs::File;
-use std::hash::{Hash, Hasher};
-use std::io::{BufReader, Read};
 use std::str::FromStr;
 use itertools::{Itertools};
-use nom::bytes::complete::{is_a, tag};
-use nom::character::complete::{space1, u32};
+use nom::bytes::complete::take;
+use nom::combinator::verify;
+use nom::character::complete::space1;
 use nom::IResult;
-use nom::multi::separated_list1;
 use nom::sequence::tuple;
 use strum_macros::{Display, EnumString};
+use crate::utilities::parsing::{lines, number};
 
-pub fn day7(path: String) {
-    let file = File::open(path).unwrap();
-    let mut buffer = BufReader::new(file);
-    let mut i = String::new();
-    _ = buffer.read_to_string(&mut i);
+pub(crate) fn part1(i: &str) -> u32 {
+    get_total_winnings_without_jokers(i)
+}
 
-    let total_winnings = get_total_winnings(&i);
+pub(crate) fn part2(i: &str) -> u32 {
+    get_total_winnings(i)
+}
 
-    println!("{}", total_winnings);
+fn get_total_winnings_without_jokers(i: &str) -> u32 {
+    let (_, hands) = parse_hands(i).unwrap();
+    total_winnings(hands)
 }
 
 fn get_total_winnings(i: &str) -> u32 {
-    let (_, mut hands) = parse_hands(&i).unwrap();
+    let (_, hands) = parse_hands(i).unwrap();
+    total_winnings(hands.into_iter().map(|h| Hand::new_with_rules(h.raw_hand, h.bid, Rules::Jokers)).collect())
+}
+
+fn total_winnings(mut hands: Vec<Hand>) -> u32 {
     hands.sort();
     let mut total_winnings = 0;
     for (idx, h) in hands.iter().enumerate() {
         let a = idx + 1;
-        println!("{}", format!("Rank: {} Type: {} Value: {} Raw: {}", idx + 1, &h.hand_type, &h.card_value, &h.raw_hand));
         total_winnings += h.bid * a as u32;
     }
 
     total_winnings
 }
 
+/// Part 1 plays jacks as ordinary cards, part 2 turns them into jokers: wild for the hand type,
+/// but the weakest card when breaking ties.
+#[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+pub(crate) enum Rules {
+    Standard,
+    Jokers,
+}
+
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, EnumString)]
-enum Card {
+pub(crate) enum Card {
     #[strum(serialize = "2")]
     Two     = 2,
     #[strum(serialize = "3")]
@@ -66,8 +77,17 @@ enum Card {
     Ace     = 8192
 }
 
+impl Card {
+    fn value(&self, rules: Rules) -> u32 {
+        match (self, rules) {
+            (Card::Jack, Rules::Standard) => 1024,
+            (card, _) => *card as u32,
+        }
+    }
+}
+
 #[derive(Display, Debug, Eq,PartialEq, Ord, PartialOrd, EnumString)]
-enum HandType {
+pub(crate) enum HandType {
     FiveOfAKind = 7,
     FourOfAKind = 6,
     FullHouse = 5,
@@ -77,13 +97,14 @@ enum HandType {
     HighCard = 1
 }
 
-#[derive(Debug, Eq, Ord)]
-struct Hand<'a> {
+#[derive(Debug, Eq)]
+pub(crate) struct Hand<'a> {
     raw_hand: &'a str,
     cards: Vec<Card>,
     card_value: u32,
     bid: u32,
     hand_type: HandType,
+    rules: Rules,
 }
 
 impl PartialEq for Hand<'_> {
@@ -95,34 +116,40 @@ impl PartialEq for Hand<'_> {
 
 impl PartialOrd for Hand<'_> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let result = if &self.hand_type.cmp(&other.hand_type) == &Ordering::Equal {
-            let mut res = Ordering::Equal;
-            for (c1, c2) in self.cards.iter().zip(&other.cards) {
-                if c1.cmp(c2) == Ordering::Equal {
-                    continue;
-                } else {
-                    res = c1.cmp(&c2);
-                    break;
-                }
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.hand_type.cmp(&other.hand_type) != Ordering::Equal {
+            return self.hand_type.cmp(&other.hand_type);
+        }
+
+        for (c1, c2) in self.cards.iter().zip(&other.cards) {
+            let (v1, v2) = (c1.value(self.rules), c2.value(other.rules));
+            if v1 != v2 {
+                return v1.cmp(&v2);
             }
-            Some(res)
-        } else {
-            Some(self.hand_type.cmp(&other.hand_type))
-        };
+        }
 
-        result
+        Ordering::Equal
     }
 }
 
 impl<'hand> Hand<'hand> {
     pub fn new(cards: &'hand str, bid: u32) -> Hand<'hand> {
+        Self::new_with_rules(cards, bid, Rules::Standard)
+    }
+
+    pub fn new_with_rules(cards: &'hand str, bid: u32, rules: Rules) -> Hand<'hand> {
         let parsed_cards: Vec<Card> = cards
             .split("")
             .filter(|x| !x.is_empty())
             .map(|c| Card::from_str(c).unwrap())
             .collect();
 
-        let mut cards_freq = parsed_cards.clone().into_iter()
+        let cards_freq = parsed_cards.clone().into_iter()
             .fold(BTreeMap::new(), |mut acc, c| {
                 *acc.entry(c).or_insert(0) += 1;
                 acc
@@ -131,44 +158,54 @@ impl<'hand> Hand<'hand> {
         let mut hand = Self {
             raw_hand: cards,
             cards: parsed_cards.clone(),
-            card_value: Self::calculate_value(parsed_cards.clone()),
+            card_value: Self::calculate_value(parsed_cards.clone(), rules),
             bid,
-            hand_type: HandType::HighCard
+            hand_type: HandType::HighCard,
+            rules,
         };
 
-        hand.hand_type = get_type(cards_freq);
+        hand.hand_type = match rules {
+            Rules::Standard => get_standard_type(cards_freq),
+            Rules::Jokers => get_type(cards_freq),
+        };
 
         hand
     }
 
-    fn calculate_value(cards: Vec<Card>) -> u32 {
+    fn calculate_value(cards: Vec<Card>, rules: Rules) -> u32 {
         let mut score = 0;
 
         for (idx, card) in cards.into_iter().enumerate() {
             let card_pos: u32 = 5 - idx as u32;
-            score += card as u32 * card_pos;
+            score += card.value(rules) * card_pos;
         }
 
         score
     }
 }
 
-fn get_type(mut cards: BTreeMap<Card, u32>) -> HandType {
+/// The hand type once every joker has been counted as whichever card makes the strongest hand.
+pub(crate) fn get_type(mut cards: BTreeMap<Card, u32>) -> HandType {
     if cards.keys().collect::<Vec<&Card>>() != vec![&Card::Jack] {
         if let Some((_, mut wildcards)) = cards.remove_entry(&Card::Jack) {
-            for (k, v) in cards.clone().iter().sorted_by(|a, b| a.1.cmp(&b.1)).rev() {
-                if wildcards <= 0 {
+            for (k, v) in cards.clone().iter().sorted_by(|a, b| a.1.cmp(b.1)).rev() {
+                if wildcards == 0 {
                     break;
                 }
 
-                let wildcards_left = ((v + wildcards) as u32).saturating_sub(5);
+                let wildcards_left = (v + wildcards).saturating_sub(5);
                 let used = wildcards - wildcards_left;
-                *cards.get_mut(&k).unwrap() += used;
+                *cards.get_mut(k).unwrap() += used;
                 wildcards = wildcards_left;
             }
         }
     }
 
+    get_standard_type(cards)
+}
+
+/// The hand type with every card taken at face value.
+pub(crate) fn get_standard_type(cards: BTreeMap<Card, u32>) -> HandType {
     let sorted: Vec<(Card, u32)> = cards.clone().into_iter()
         .sorted_by(|a, b| a.1.cmp(&b.1)).rev()
         .collect();
@@ -195,14 +232,13 @@ fn get_type(mut cards: BTreeMap<Card, u32>) -> HandType {
     }
 }
 
-fn parse_hand(i: &str) -> IResult<&str, Hand> {
-    tuple((is_a("23456789TJQKA"), space1, u32))(i).map(|(i, (cards, _, bid))| (i, Hand::new(cards, bid)))
+fn parse_hand(i: &str) -> IResult<&str, Hand<'_>> {
+    let cards = verify(take(5usize), |cards: &str| cards.chars().all(|c| "23456789TJQKA".contains(c)));
+    tuple((cards, space1, number))(i).map(|(i, (cards, _, bid))| (i, Hand::new(cards, bid)))
 }
 
-fn parse_hands(i: &str) -> IResult<&str, Vec<Hand>> {
-    let (i, hand) = separated_list1(tag("\n"), parse_hand)(i)?;
-
-    Ok((i, hand))
+pub(crate) fn parse_hands(i: &str) -> IResult<&str, Vec<Hand<'_>>> {
+    lines(parse_hand)(i)
 }
 
 #[cfg(test)]
@@ -216,6 +252,13 @@ mod test {
         assert_eq!(parse_hand("AAKJQ 303").unwrap(), ("", Hand::new("AAKJQ", 303)));
     }
 
+    #[test]
+    fn should_reject_hands_without_five_cards() {
+        assert!(parse_hand("AAAAAAA 1").is_err());
+        assert!(parse_hand("AAA 1").is_err());
+        assert!(parse_hand("AAAAX 1").is_err());
+    }
+
     #[test]
     fn should_parse_multiple_hands_correctly() {
         assert_eq!(parse_hands("A2345 101\nAAKKQ 202\nAAKJQ 303").unwrap(), (
@@ -279,7 +322,19 @@ KTJJT 220
 QQQJA 483
 "#;
 
-        assert_eq!(get_total_winnings(hands), 6440);
+        assert_eq!(get_total_winnings_without_jokers(hands), 6440);
+    }
+
+    #[test]
+    fn should_calculate_correct_winnings_with_jokers() {
+        let hands = r#"32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483
+"#;
+
+        assert_eq!(get_total_winnings(hands), 5905);
     }
 
     #[test]
@@ -299,7 +354,7 @@ AAKQJ 31
 AKQJT 41
 23456 43
 "#;
-        assert_eq!(get_total_winnings(hands), 1343);
+        assert_eq!(get_total_winnings_without_jokers(hands), 1343);
     }
 
     #[test]
@@ -1,105 +1,132 @@
-use std::collections::HashMap;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use itertools::Itertools;
-use nom::bytes::complete::{is_a, take, take_while};
-use nom::character::complete::{alpha1, newline};
+use nom::bytes::complete::{is_a, take};
 use nom::bytes::complete::tag;
 use nom::IResult;
-use nom::combinator::map_res;
-use nom::multi::many1;
-use nom::sequence::{pair, tuple};
+use nom::combinator::verify;
+use nom::sequence::{delimited, separated_pair};
 use strum_macros::EnumString;
-use crate::utilities::load_input;
+use crate::utilities::graph::{find_cycle, Cycle, Graph, NodeId};
+use crate::utilities::math::crt;
+use crate::utilities::parsing::{blank_line, key_value, lines};
 
-pub fn day8(path: String) {
-    let i = load_input(path);
-    let steps_to_way_out = calculate_steps_to_way_out(&i);
+pub(crate) fn part1(i: &str) -> usize {
+    calculate_steps_to_way_out(i)
+}
 
-    println!("Part 1: {}", steps_to_way_out);
+pub(crate) fn part2(i: &str) -> u64 {
+    calculate_steps_for_ghosts(i).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    // Part 2
-    let steps = calculate_steps_to_way_out_pt2(&i);
-    println!("Part 2: {}", steps);
+#[derive(Debug, PartialEq)]
+pub(crate) enum GhostError {
+    NeverAtEnd(String),
+    NeverTogether,
 }
 
-fn calculate_steps_to_way_out_pt2(i: &str) -> usize {
-    let (i, (directions, nodes)) = parse_map_file(i).unwrap();
+impl Display for GhostError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GhostError::NeverAtEnd(start) => write!(f, "the ghost starting on {} never reaches a node ending in Z", start),
+            GhostError::NeverTogether => write!(f, "the ghosts are never all on nodes ending in Z at the same time"),
+        }
+    }
+}
 
-    let node_tree = nodes.iter().fold(MapNodeTree::new(), |mut tree, node| {
-        _ = tree.insert_node(node);
-        tree
-    });
+fn calculate_steps_to_way_out(i: &str) -> usize {
+    let (_, (directions, nodes)) = parse_map_file(i).unwrap();
+    let node_tree = MapNodeTree::from_nodes(&nodes);
 
-    let mut ids: Vec<MapNodeId> = node_tree.nodes.keys().filter(|x| x.ends_with("A")).map(|x| x.to_owned()).collect();
-    let mut i = 0;
-    let mut loop_sizes = HashMap::new();
-    // while !ids.clone().iter().all(|x| x.ends_with("Z")) {
-    while ids.len() > 0 {
-        let curr_direction = &directions[i % directions.len()];
+    steps_until(&node_tree, &directions, "AAA", |id| id == "ZZZ").unwrap()
+}
 
-        for (idx, id) in ids.clone().into_iter().enumerate() {
-            if id.ends_with("Z") {
-                loop_sizes.insert(id, i);
-                ids.remove(idx);
-                break;
+/// Every ghost starts on a node ending in `A`. Until the last of them has settled into its cycle the
+/// walks are simply checked step by step, after that each ghost is on an end node at certain residues
+/// of its cycle length, so the first time they all are is the earliest solution of the congruences.
+fn calculate_steps_for_ghosts(i: &str) -> Result<u64, GhostError> {
+    let (_, (directions, nodes)) = parse_map_file(i).unwrap();
+    let node_tree = MapNodeTree::from_nodes(&nodes);
+
+    let ghosts = node_tree.graph.nodes()
+        .filter(|(_, id)| id.ends_with('A'))
+        .map(|(start, id)| {
+            let visits = node_tree.visits(&directions, start, |id| id.ends_with('Z'));
+            if visits.before_cycle.is_empty() && visits.in_cycle.is_empty() {
+                return Err(GhostError::NeverAtEnd(id.to_string()));
             }
+            Ok(visits)
+        })
+        .collect::<Result<Vec<Visits>, GhostError>>()?;
 
-            let node = node_tree.get_node(&id);
-            ids[idx] = match curr_direction {
-                Direction::L => node.left.unwrap(),
-                Direction::R => node.right.unwrap(),
-            };
-        }
-        i += 1;
-        // dbg!(i);
-        // dbg!(&ids);
-        dbg!(&loop_sizes);
-
+    let settled = ghosts.iter().map(|g| g.cycle.offset).max().unwrap_or(0);
+    if let Some(steps) = (0..settled).find(|steps| ghosts.iter().all(|g| g.at_end(*steps))) {
+        return Ok(steps as u64);
+    }
 
+    // Fold the ghosts in one at a time, keeping each residue of the combined cycle at which every
+    // ghost so far is on an end node. Ghosts sharing a loop share residues, so the set stays small.
+    let (mut residues, mut modulus) = (BTreeSet::from([0]), 1);
+    for ghost in ghosts.iter() {
+        let length = ghost.cycle.length as u64;
+        let ends = ghost.in_cycle.iter().map(|steps| steps % ghost.cycle.length).map(|r| r as u64).collect::<BTreeSet<u64>>();
+        let (mut combined, mut combined_modulus) = (BTreeSet::new(), modulus);
+        for (residue, end) in residues.iter().cartesian_product(ends.iter()) {
+            if let Some((r, m)) = crt(&[(*residue, modulus), (*end, length)]) {
+                combined.insert(r);
+                combined_modulus = m;
+            }
+        }
+        if combined.is_empty() {
+            return Err(GhostError::NeverTogether);
+        }
+        (residues, modulus) = (combined, combined_modulus);
     }
-    let lcm = lowest_common_muliple(loop_sizes.values().collect(), 0);
-    dbg!(lcm);
-    i
-}
 
-fn lowest_common_muliple(input: Vec<&usize>, idx: usize) -> usize {
+    let settled = settled as u64;
+    residues.into_iter()
+        .filter_map(|residue| {
+            if residue >= settled {
+                return Some(residue);
+            }
+            (settled - residue).div_ceil(modulus).checked_mul(modulus)?.checked_add(residue)
+        })
+        .min()
+        .ok_or(GhostError::NeverTogether)
+}
 
+/// Steps taken from `start` until reaching a node for which `is_end` holds, or `None` if the walk
+/// hits a node that was never defined or comes back round to where it was without getting there.
+fn steps_until(node_tree: &MapNodeTree, directions: &[Direction], start: MapNodeId, is_end: impl Fn(MapNodeId) -> bool) -> Option<usize> {
+    let visits = node_tree.visits(directions, node_tree.graph.id(&start)?, is_end);
+    visits.before_cycle.into_iter().chain(visits.in_cycle).next()
 }
 
-fn gcd(a: usize, b: usize) -> usize {
-    // Find Minimum of a and b
-    if b == 0 {
-        return a;
-    }
+/// Where a walk is: on a node, part way through the directions. `None` once it has stepped onto a node
+/// that was never defined.
+type Position = Option<(NodeId, usize)>;
 
-    gcd(b, a % b)
+/// The steps at which a walk is on an end node. Those in the cycle come round again every
+/// `cycle.length` steps, the ones before it never do.
+struct Visits {
+    cycle: Cycle,
+    before_cycle: Vec<usize>,
+    in_cycle: Vec<usize>,
 }
 
-fn calculate_steps_to_way_out(i: &str) -> usize {
-    let (i, (directions, nodes)) = parse_map_file(i).unwrap();
-
-    let node_tree = nodes.iter().fold(MapNodeTree::new(), |mut tree, node| {
-        _ = tree.insert_node(node);
-        tree
-    });
-
-    let mut curr_node_id: MapNodeId = "AAA";
-    let mut i = 0;
-    while curr_node_id != "ZZZ" {
-        let curr_direction = &directions[i % directions.len()];
-        let node = node_tree.get_node(&curr_node_id);
-        curr_node_id = match curr_direction {
-            Direction::L => node.left.unwrap(),
-            Direction::R => node.right.unwrap(),
-        };
-        i += 1;
+impl Visits {
+    fn at_end(&self, steps: usize) -> bool {
+        if steps < self.cycle.offset {
+            self.before_cycle.contains(&steps)
+        } else {
+            self.in_cycle.contains(&(self.cycle.offset + (steps - self.cycle.offset) % self.cycle.length))
+        }
     }
-
-    i
 }
 
 #[derive(EnumString)]
-enum Direction {
+pub(crate) enum Direction {
     #[strum(serialize = "L")]
     L,
     #[strum(serialize = "R")]
@@ -108,58 +135,68 @@ enum Direction {
 
 type MapNodeId<'arena> = &'arena str;
 
+/// The network as a graph where each defined node's successors are `[left, right]`.
 struct MapNodeTree<'arena> {
-    nodes: HashMap<&'arena str, MapNode<'arena>>,
+    graph: Graph<MapNodeId<'arena>>,
 }
 
 impl<'arena> MapNodeTree<'arena> {
-    pub fn new() -> Self {
-        Self {
-            nodes: HashMap::new()
+    pub fn from_nodes(nodes: &[MapNode<'arena>]) -> Self {
+        let mut graph = Graph::new();
+        for node in nodes.iter() {
+            graph.add_edge(node.node_id, node.left);
+            graph.add_edge(node.node_id, node.right);
         }
-    }
 
-    pub fn get_node(&'arena self, node_idx: MapNodeId) -> &'arena MapNode {
-        match &self.nodes.get(node_idx) {
-            None => panic!("Couldnt find {}", node_idx),
-            Some(n) => *n
-        }
+        Self { graph }
     }
 
-    // pub fn update_node(&mut self, node_idx: &'arena MapNodeId, updated: &'arena MapNode) {
-    //     if let Some(node) = self.nodes.get_mut(node_idx) {
-    //         *node = updated.clone();
-    //     }
-    // }
+    pub fn next(&self, node: NodeId, direction: &Direction) -> Option<NodeId> {
+        let edge = match direction {
+            Direction::L => 0,
+            Direction::R => 1,
+        };
+        self.graph.successors(node).get(edge).copied()
+    }
 
-    pub fn insert_node(&mut self, node: &'arena MapNode) -> MapNodeId {
-        self.nodes.insert(&node.node_id, node.clone());
-        node.node_id
+    fn step(&self, directions: &[Direction], position: &Position) -> Position {
+        let (node, idx) = (*position)?;
+        Some((self.next(node, &directions[idx])?, (idx + 1) % directions.len()))
     }
 
+    /// Walks from `start` until it repeats, which covers every step it will ever be on an end node at.
+    fn visits(&self, directions: &[Direction], start: NodeId, is_end: impl Fn(MapNodeId) -> bool) -> Visits {
+        let cycle = find_cycle(Some((start, 0)), |position| self.step(directions, position));
+        let mut visits = Visits { cycle, before_cycle: vec![], in_cycle: vec![] };
+
+        let mut position = Some((start, 0));
+        for steps in 0..cycle.offset + cycle.length {
+            if position.is_some_and(|(node, _)| is_end(self.graph.label(node))) {
+                if steps < cycle.offset { &mut visits.before_cycle } else { &mut visits.in_cycle }.push(steps);
+            }
+            position = self.step(directions, &position);
+        }
+
+        visits
+    }
 }
 
 #[derive(Debug, Clone)]
-struct MapNode<'arena> {
+pub(crate) struct MapNode<'arena> {
     node_id: &'arena str,
-    left: Option<MapNodeId<'arena>>,
-    right: Option<MapNodeId<'arena>>
+    left: MapNodeId<'arena>,
+    right: MapNodeId<'arena>,
 }
 
 impl<'arena> MapNode<'arena> {
     pub fn new(node_id: &'arena str, left: &'arena str, right: &'arena str ) -> Self {
-        Self {
-            node_id,
-            left: Some(left),
-            right: Some(right),
-        }
+        Self { node_id, left, right }
     }
 }
 
 fn parse_directions(i: &str) -> IResult<&str, Vec<Direction>> {
-
-    pair(is_a("LR"), newline)(i)
-        .map(|(i, (mut directions, _))| {
+    is_a("LR")(i)
+        .map(|(i, directions)| {
             let d: Vec<Direction> = directions.split("").filter(|x| !x.is_empty()).map(|x| {
                 Direction::from_str(x).unwrap()
             }).collect();
@@ -168,29 +205,71 @@ fn parse_directions(i: &str) -> IResult<&str, Vec<Direction>> {
 }
 
 fn parse_identifier(i: &str) -> IResult<&str, &str> {
-    let (i, alphabetic_str) = take(3usize)(i)?;
-    let (_, parsed) = alpha1(alphabetic_str)?;
-
-    Ok((i, parsed))
+    verify(take(3usize), |id: &str| id.chars().all(|c| c.is_ascii_alphanumeric()))(i)
 }
 
-fn parse_map_node(i: &str) -> IResult<&str, MapNode> {
-    tuple((parse_identifier, tag(" = ("), parse_identifier, tag(", "), parse_identifier, tag(")"), newline))(i)
-        .map(|(i, (curr, _, left, _, right, _, _))| (i, MapNode::new(curr, left, right)))
+fn parse_map_node(i: &str) -> IResult<&str, MapNode<'_>> {
+    let edges = delimited(tag("("), separated_pair(parse_identifier, tag(", "), parse_identifier), tag(")"));
+    key_value(parse_identifier, "=", edges)(i)
+        .map(|(i, (curr, (left, right)))| (i, MapNode::new(curr, left, right)))
 }
 
-fn parse_map_nodes(i: &str) -> IResult<&str, Vec<MapNode>> {
-    many1(parse_map_node)(i)
+pub(crate) fn parse_map_file(i: &str) -> IResult<&str, (Vec<Direction>, Vec<MapNode<'_>>)> {
+    separated_pair(parse_directions, blank_line, lines(parse_map_node))(i)
 }
 
-fn parse_map_file(i: &str) -> IResult<&str, (Vec<Direction>, Vec<MapNode>)> {
-    let (i, directions) = parse_directions(i)?;
-    let (i, (_)) = newline(i)?;
-    let (i, mut nodes) = parse_map_nodes(i)?;
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_follow_directions_to_the_exit() {
+        let input = "LLR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)";
+        assert_eq!(part1(input), 6);
+    }
+
+    #[test]
+    fn ghosts_should_meet_at_lowest_common_multiple() {
+        let input = "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\n22A = (22B, XXX)\n22B = (22C, 22C)\n22C = (22Z, 22Z)\n22Z = (22B, 22B)\nXXX = (XXX, XXX)\n";
+        assert_eq!(part2(input), 6);
+    }
+
+    #[test]
+    fn ghosts_should_meet_when_cycles_start_late() {
+        // 11A reaches 11Z on every even step from 2, 22A on steps 1, 4, 7, ... so an lcm of 2 and 1 would be wrong.
+        let input = "L\n\n11A = (11B, 11B)\n11B = (11Z, 11Z)\n11Z = (11C, 11C)\n11C = (11Z, 11Z)\n22A = (22Z, 22Z)\n22Z = (22B, 22B)\n22B = (22C, 22C)\n22C = (22Z, 22Z)";
+        assert_eq!(calculate_steps_for_ghosts(input), Ok(4));
+    }
+
+    #[test]
+    fn ghosts_should_meet_quickly_when_each_is_at_an_end_on_every_step() {
+        // Every ghost loops on ZZZ for all 60 directions, 60 residues each: far too many to try every combination.
+        let directions = "LR".repeat(30);
+        let ghosts = (b'B'..=b'M').map(|c| format!("{}{}A = (ZZZ, ZZZ)\n", c as char, c as char)).collect::<String>();
+        let input = format!("{}\n\n{}ZZZ = (ZZZ, ZZZ)", directions, ghosts);
+        assert_eq!(calculate_steps_for_ghosts(&input), Ok(1));
+    }
+
+    #[test]
+    fn should_report_ghosts_that_never_meet() {
+        let stuck = "L\n\n11A = (11B, 11B)\n11B = (11A, 11A)\n22A = (22Z, 22Z)\n22Z = (22Z, 22Z)";
+        assert_eq!(calculate_steps_for_ghosts(stuck), Err(GhostError::NeverAtEnd("11A".to_owned())));
+
+        let out_of_step = "L\n\n11A = (11Z, 11Z)\n11Z = (11B, 11B)\n11B = (11Z, 11Z)\n22A = (22B, 22B)\n22B = (22C, 22C)\n22C = (22D, 22D)\n22D = (22Z, 22Z)\n22Z = (22B, 22B)";
+        assert_eq!(calculate_steps_for_ghosts(out_of_step), Err(GhostError::NeverTogether));
+    }
 
-    // // TODO: Whats going on here?
-    // let (_, (last_node)) = parse_map_node(i)?;
-    // nodes.push(last_node);
+    #[test]
+    fn should_reject_malformed_identifiers() {
+        assert!(parse_identifier("A-B").is_err());
+        assert_eq!(parse_identifier("11Z)"), Ok((")", "11Z")));
+    }
 
-    Ok((i, (directions, nodes)))
-}
\ No newline at end of file
+    #[test]
+    fn should_give_up_on_unreachable_exit() {
+        let (_, (directions, nodes)) = parse_map_file("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)").unwrap();
+        let node_tree = MapNodeTree::from_nodes(&nodes);
+        assert_eq!(steps_until(&node_tree, &directions, "AAA", |id| id == "ZZZ"), None);
+        assert_eq!(steps_until(&node_tree, &directions, "QQQ", |id| id == "ZZZ"), None);
+    }
+}
@@ -5,4 +5,100 @@ pub(crate) mod day4;
 pub(crate) mod day5;
 pub(crate) mod day6;
 pub(crate) mod day7;
-pub(crate) mod day8;
\ No newline at end of file
+pub(crate) mod day8;
+
+use std::time::{Duration, Instant};
+use crate::utilities::fingerprint_all;
+
+pub(crate) const BUILTIN: &str = "builtin";
+
+/// The shared code built-in solvers are written on top of, folded into every built-in solver's version
+/// so that changing a utility invalidates the answers it helped produce.
+const SHARED_SOURCES: [&str; 7] = [
+    include_str!("../utilities/mod.rs"),
+    include_str!("../utilities/graph.rs"),
+    include_str!("../utilities/grid.rs"),
+    include_str!("../utilities/input.rs"),
+    include_str!("../utilities/interval.rs"),
+    include_str!("../utilities/math.rs"),
+    include_str!("../utilities/parsing.rs"),
+];
+
+/// A solver for a single part of a day, taking the raw puzzle input and returning its answer.
+pub(crate) struct Solver {
+    pub day: u8,
+    pub part: u8,
+    /// Tells apart several variants of the same day/part, e.g. a brute force and an optimised solver,
+    /// or a built-in one and one loaded from a plugin.
+    pub name: String,
+    /// Fingerprint of the solver's implementation, used to invalidate cached answers when it changes.
+    /// Built-in solvers cover the crate version, their day's source and the shared utilities.
+    pub version: u64,
+    pub solve: Box<dyn Fn(&str) -> String>,
+}
+
+/// The answer a solver produced for an input, and how long it took to produce it.
+pub(crate) struct Outcome {
+    pub answer: String,
+    pub elapsed: Duration,
+    pub cached: bool,
+}
+
+impl Solver {
+    pub fn builtin(day: u8, part: u8, source: &'static str, solve: fn(&str) -> String) -> Self {
+        Self {
+            day,
+            part,
+            name: BUILTIN.to_owned(),
+            version: fingerprint_all(
+                [env!("CARGO_PKG_VERSION"), source].into_iter().chain(SHARED_SOURCES).map(str::as_bytes)
+            ),
+            solve: Box::new(solve),
+        }
+    }
+
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = name.to_owned();
+        self
+    }
+
+    pub fn run(&self, i: &str) -> Outcome {
+        let start = Instant::now();
+        let answer = (self.solve)(i);
+
+        Outcome { answer, elapsed: start.elapsed(), cached: false }
+    }
+}
+
+/// The first registered variant of each day/part, which is the one used to produce answers.
+pub(crate) fn primary(solvers: &[Solver]) -> Vec<&Solver> {
+    solvers.iter().enumerate()
+        .filter(|(idx, s)| !solvers[..*idx].iter().any(|o| o.day == s.day && o.part == s.part))
+        .map(|(_, s)| s)
+        .collect()
+}
+
+/// Every implemented day/part in puzzle order, with any alternative variants after the primary one.
+pub(crate) fn registry() -> Vec<Solver> {
+    vec![
+        Solver::builtin(1, 1, include_str!("day1.rs"), |i| day1::part1(i).to_string()),
+        Solver::builtin(1, 2, include_str!("day1.rs"), |i| day1::part2(i).to_string()),
+        Solver::builtin(2, 1, include_str!("day2.rs"), |i| day2::part1(i).to_string()),
+        Solver::builtin(2, 2, include_str!("day2.rs"), |i| day2::part2(i).to_string()),
+        Solver::builtin(3, 1, include_str!("day3.rs"), |i| day3::part1(i).to_string()),
+        Solver::builtin(3, 2, include_str!("day3.rs"), |i| day3::part2(i).to_string()),
+        Solver::builtin(4, 1, include_str!("day4.rs"), |i| day4::part1(i).to_string()),
+        Solver::builtin(4, 2, include_str!("day4.rs"), |i| day4::part2(i).to_string()),
+        Solver::builtin(5, 1, include_str!("day5.rs"), |i| day5::part1(i).to_string()),
+        Solver::builtin(5, 2, include_str!("day5.rs"), |i| day5::part2(i).to_string()),
+        Solver::builtin(5, 2, include_str!("day5.rs"), |i| day5::part2_brute_force(i).to_string()).named("brute_force"),
+        Solver::builtin(6, 1, include_str!("day6.rs"), |i| day6::part1(i).to_string()),
+        Solver::builtin(6, 1, include_str!("day6.rs"), |i| day6::part1_closed_form(i).to_string()).named("closed_form"),
+        Solver::builtin(6, 2, include_str!("day6.rs"), |i| day6::part2(i).to_string()),
+        Solver::builtin(6, 2, include_str!("day6.rs"), |i| day6::part2_closed_form(i).to_string()).named("closed_form"),
+        Solver::builtin(7, 1, include_str!("day7.rs"), |i| day7::part1(i).to_string()),
+        Solver::builtin(7, 2, include_str!("day7.rs"), |i| day7::part2(i).to_string()),
+        Solver::builtin(8, 1, include_str!("day8.rs"), |i| day8::part1(i).to_string()),
+        Solver::builtin(8, 2, include_str!("day8.rs"), |i| day8::part2(i).to_string()),
+    ]
+}
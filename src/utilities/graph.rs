@@ -0,0 +1,223 @@
+//! Directed graphs over interned labels, and cycle detection for any deterministic walk.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+pub(crate) type NodeId = usize;
+
+/// A directed graph whose nodes are identified by labels, interned to dense [`NodeId`]s.
+/// Each node's successors keep the order their edges were added in.
+#[derive(Debug, Clone)]
+pub(crate) struct Graph<L> {
+    labels: Vec<L>,
+    ids: HashMap<L, NodeId>,
+    edges: Vec<Vec<NodeId>>,
+}
+
+/// Where a walk starts repeating itself: after `offset` steps it enters a loop of `length` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Cycle {
+    pub offset: usize,
+    pub length: usize,
+}
+
+impl<L: Hash + Eq + Clone> Graph<L> {
+    pub fn new() -> Self {
+        Self { labels: vec![], ids: HashMap::new(), edges: vec![] }
+    }
+
+    /// The id for `label`, adding it as a node without edges if it's new.
+    pub fn intern(&mut self, label: L) -> NodeId {
+        if let Some(id) = self.ids.get(&label) {
+            return *id;
+        }
+
+        let id = self.labels.len();
+        self.labels.push(label.clone());
+        self.ids.insert(label, id);
+        self.edges.push(vec![]);
+        id
+    }
+
+    pub fn id(&self, label: &L) -> Option<NodeId> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, id: NodeId) -> &L {
+        &self.labels[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &L)> + '_ {
+        self.labels.iter().enumerate()
+    }
+
+    pub fn add_edge(&mut self, from: L, to: L) {
+        let (from, to) = (self.intern(from), self.intern(to));
+        self.edges[from].push(to);
+    }
+
+    pub fn successors(&self, id: NodeId) -> &[NodeId] {
+        self.edges.get(id).map_or(&[], |e| e.as_slice())
+    }
+
+    /// Every node reachable from `start` with its distance in edges, nearest first.
+    #[allow(dead_code)]
+    pub fn bfs(&self, start: NodeId) -> Vec<(NodeId, usize)> {
+        let mut visited = vec![false; self.len()];
+        let mut queue = VecDeque::from([(start, 0)]);
+        let mut order = vec![];
+        visited[start] = true;
+        while let Some((node, distance)) = queue.pop_front() {
+            order.push((node, distance));
+            for next in self.successors(node) {
+                if !visited[*next] {
+                    visited[*next] = true;
+                    queue.push_back((*next, distance + 1));
+                }
+            }
+        }
+
+        order
+    }
+
+    /// Every node reachable from `start`, in depth-first pre-order.
+    #[allow(dead_code)]
+    pub fn dfs(&self, start: NodeId) -> Vec<NodeId> {
+        let mut visited = vec![false; self.len()];
+        let mut stack = vec![start];
+        let mut order = vec![];
+        while let Some(node) = stack.pop() {
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            order.push(node);
+            stack.extend(self.successors(node).iter().rev().filter(|n| !visited[**n]));
+        }
+
+        order
+    }
+
+    /// Strongly connected components, each sorted by id, found without recursion (Kosaraju).
+    #[allow(dead_code)]
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut visited = vec![false; self.len()];
+        let mut finished = vec![];
+        for root in 0..self.len() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![(root, 0)];
+            while let Some((node, edge)) = stack.pop() {
+                match self.edges[node].get(edge) {
+                    Some(next) => {
+                        stack.push((node, edge + 1));
+                        if !visited[*next] {
+                            visited[*next] = true;
+                            stack.push((*next, 0));
+                        }
+                    }
+                    None => finished.push(node),
+                }
+            }
+        }
+
+        let mut reversed = vec![vec![]; self.len()];
+        for (from, targets) in self.edges.iter().enumerate() {
+            targets.iter().for_each(|to| reversed[*to].push(from));
+        }
+
+        let mut assigned = vec![false; self.len()];
+        let mut components = vec![];
+        for root in finished.into_iter().rev() {
+            if assigned[root] {
+                continue;
+            }
+            assigned[root] = true;
+            let mut component = vec![];
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                component.push(node);
+                for previous in reversed[node].iter() {
+                    if !assigned[*previous] {
+                        assigned[*previous] = true;
+                        stack.push(*previous);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+
+        components
+    }
+}
+
+/// Follows `step` from `start` until some state comes round again.
+pub(crate) fn find_cycle<S: Hash + Eq + Clone>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut seen = HashMap::new();
+    let mut state = start;
+    for i in 0.. {
+        if let Some(first) = seen.insert(state.clone(), i) {
+            return Cycle { offset: first, length: i - first };
+        }
+        state = step(&state);
+    }
+
+    unreachable!("a walk over finitely many states always repeats")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn graph(edges: &[(&'static str, &'static str)]) -> Graph<&'static str> {
+        edges.iter().fold(Graph::new(), |mut g, (from, to)| {
+            g.add_edge(*from, *to);
+            g
+        })
+    }
+
+    fn labels(g: &Graph<&'static str>, ids: &[NodeId]) -> Vec<&'static str> {
+        ids.iter().map(|id| *g.label(*id)).collect()
+    }
+
+    #[test]
+    fn should_intern_labels_once() {
+        let mut g = graph(&[("a", "b"), ("b", "a")]);
+        assert_eq!(g.len(), 2);
+        assert_eq!(g.intern("a"), 0);
+        assert_eq!(g.id(&"c"), None);
+        assert_eq!(labels(&g, g.successors(0)), vec!["b"]);
+    }
+
+    #[test]
+    fn should_traverse_breadth_and_depth_first() {
+        let g = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("d", "a"), ("e", "a")]);
+        let a = g.id(&"a").unwrap();
+        let bfs = g.bfs(a);
+        assert_eq!(labels(&g, &bfs.iter().map(|(n, _)| *n).collect::<Vec<_>>()), vec!["a", "b", "c", "d"]);
+        assert_eq!(bfs.last().unwrap().1, 2);
+        assert_eq!(labels(&g, &g.dfs(a)), vec!["a", "b", "d", "c"]);
+    }
+
+    #[test]
+    fn should_find_strongly_connected_components() {
+        let g = graph(&[("a", "b"), ("b", "c"), ("c", "a"), ("c", "d"), ("d", "e"), ("e", "d"), ("e", "f")]);
+        let mut components = g.strongly_connected_components().iter().map(|c| labels(&g, c)).collect::<Vec<_>>();
+        components.sort();
+        assert_eq!(components, vec![vec!["a", "b", "c"], vec!["d", "e"], vec!["f"]]);
+    }
+
+    #[test]
+    fn should_find_cycle_offset_and_length() {
+        assert_eq!(find_cycle(0u32, |n| if *n < 3 { n + 1 } else { 1 }), Cycle { offset: 1, length: 3 });
+        assert_eq!(find_cycle(7u32, |n| *n), Cycle { offset: 0, length: 1 });
+    }
+}
@@ -0,0 +1,200 @@
+//! A 2D grid of cells: neighbours, row and column views, and runs of matching cells along a row.
+
+use std::fmt::{Display, Formatter};
+use nom::character::complete::{multispace0, none_of};
+use nom::combinator::all_consuming;
+use nom::sequence::terminated;
+use crate::utilities::parsing::grid;
+
+const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const SURROUNDING: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// A rectangular grid of cells addressed by `(row, col)`, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+/// A horizontal span of neighbouring cells on one row, covering columns `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Run {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Grid<char> {
+    /// One cell per character, one row per line, failing unless every row is the same width.
+    pub fn parse_chars(i: &str) -> Option<Self> {
+        all_consuming(terminated(grid(none_of("\r\n")), multispace0))(i)
+            .ok()
+            .and_then(|(_, rows)| Self::from_rows(rows))
+    }
+}
+
+impl<T> Grid<T> {
+    /// Fails unless every row is the same width.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |r| r.len());
+        if rows.iter().any(|r| r.len() != width) {
+            return None;
+        }
+
+        Some(Self { width, height, cells: rows.into_iter().flatten().collect() })
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if row < self.height && col < self.width { self.cells.get(row * self.width + col) } else { None }
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        (row < self.height).then(|| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.height).filter_map(move |r| self.row(r))
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> + '_ {
+        (0..self.height).filter_map(move |r| self.get(r, col))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.cells.iter().enumerate().map(move |(idx, cell)| ((idx / self.width, idx % self.width), cell))
+    }
+
+    /// Positions above, left, right and below, skipping any off the edge of the grid.
+    #[allow(dead_code)]
+    pub fn neighbours4(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        ORTHOGONAL.iter().filter_map(move |offset| self.offset(row, col, *offset))
+    }
+
+    /// All eight surrounding positions row by row, skipping any off the edge of the grid.
+    pub fn neighbours8(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        SURROUNDING.iter().filter_map(move |offset| self.offset(row, col, *offset))
+    }
+
+    fn offset(&self, row: usize, col: usize, (d_row, d_col): (isize, isize)) -> Option<(usize, usize)> {
+        let row = row.checked_add_signed(d_row)?;
+        let col = col.checked_add_signed(d_col)?;
+        (row < self.height && col < self.width).then_some((row, col))
+    }
+
+    /// The longest run of cells matching `matches` through `(row, col)`, if that cell matches.
+    pub fn run_at(&self, row: usize, col: usize, matches: impl Fn(&T) -> bool) -> Option<Run> {
+        let cells = self.row(row)?;
+        if !matches(cells.get(col)?) {
+            return None;
+        }
+
+        let start = cells[..col].iter().rposition(|c| !matches(c)).map_or(0, |p| p + 1);
+        let end = cells[col..].iter().position(|c| !matches(c)).map_or(self.width, |p| col + p);
+        Some(Run { row, start, end })
+    }
+
+    /// Every maximal run of cells matching `matches`, row by row.
+    #[allow(dead_code)]
+    pub fn runs(&self, matches: impl Fn(&T) -> bool) -> Vec<Run> {
+        let mut runs = vec![];
+        for row in 0..self.height {
+            let mut col = 0;
+            while col < self.width {
+                match self.run_at(row, col, &matches) {
+                    Some(run) => {
+                        col = run.end;
+                        runs.push(run);
+                    }
+                    None => col += 1,
+                }
+            }
+        }
+
+        runs
+    }
+
+    pub fn cells_in(&self, run: Run) -> &[T] {
+        &self.cells[run.row * self.width + run.start..run.row * self.width + run.end]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (idx, row) in self.rows().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_reject_ragged_rows() {
+        assert!(Grid::parse_chars("ab\ncd\n").is_some());
+        assert!(Grid::parse_chars("ab\nc").is_none());
+        assert!(Grid::from_rows(vec![vec![1, 2], vec![3]]).is_none());
+    }
+
+    #[test]
+    fn should_bounds_check_access() {
+        let grid = Grid::parse_chars("abc\ndef").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(1, 2), Some(&'f'));
+        assert_eq!(grid.get(0, 3), None);
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+    }
+
+    #[test]
+    fn should_skip_neighbours_off_the_edge() {
+        let grid = Grid::parse_chars("abc\ndef\nghi").unwrap();
+        assert_eq!(grid.neighbours4(0, 0).collect::<Vec<_>>(), vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours8(0, 0).collect::<Vec<_>>(), vec![(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(grid.neighbours8(1, 1).count(), 8);
+        assert_eq!(grid.neighbours4(2, 2).collect::<Vec<_>>(), vec![(1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn should_extract_horizontal_runs() {
+        let grid = Grid::parse_chars("12.3\n.45.").unwrap();
+        let digits = |c: &char| c.is_ascii_digit();
+        assert_eq!(
+            grid.runs(digits),
+            vec![Run { row: 0, start: 0, end: 2 }, Run { row: 0, start: 3, end: 4 }, Run { row: 1, start: 1, end: 3 }]
+        );
+        assert_eq!(grid.run_at(1, 2, digits), Some(Run { row: 1, start: 1, end: 3 }));
+        assert_eq!(grid.run_at(1, 0, digits), None);
+        assert_eq!(grid.cells_in(Run { row: 1, start: 1, end: 3 }), &['4', '5']);
+    }
+
+    #[test]
+    fn should_print_rows_on_separate_lines() {
+        let grid = Grid::from_rows(vec![vec![1, 2], vec![3, 4]]).unwrap();
+        assert_eq!(grid.to_string(), "12\n34");
+    }
+}
@@ -0,0 +1,175 @@
+//! Puzzle inputs memory-mapped from disk rather than read into a `String`, so a solver that walks
+//! its input line by line needs no more memory for a multi-gigabyte input than for the real one.
+
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::ops::Deref;
+use std::path::Path;
+use memmap2::Mmap;
+use crate::seal::{is_sealed, unseal_bytes, SealError, Secret, SEALED_EXTENSION};
+
+/// A whole puzzle input, checked once to be valid UTF-8 and then borrowed as a `&str`.
+pub(crate) struct Input {
+    bytes: Bytes,
+}
+
+enum Bytes {
+    Mapped(Mmap),
+    /// Empty files can't be mapped, and sealed inputs only exist in memory once decrypted.
+    Owned(Vec<u8>),
+}
+
+#[derive(Debug)]
+pub(crate) enum InputError {
+    Io(std::io::Error),
+    Seal(SealError),
+    NotUtf8,
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Io(e) => write!(f, "{}", e),
+            InputError::Seal(e) => write!(f, "{}", e),
+            InputError::NotUtf8 => write!(f, "input is not valid UTF-8"),
+        }
+    }
+}
+
+impl From<std::io::Error> for InputError {
+    fn from(value: std::io::Error) -> Self {
+        InputError::Io(value)
+    }
+}
+
+impl From<SealError> for InputError {
+    fn from(value: SealError) -> Self {
+        InputError::Seal(value)
+    }
+}
+
+impl Input {
+    /// Maps the input at `path`, transparently decrypting it when only a sealed copy (`<path>.sealed`)
+    /// exists or when the file at `path` is itself sealed.
+    pub fn open(path: &str) -> Result<Self, InputError> {
+        let sealed_path = format!("{}{}", path, SEALED_EXTENSION);
+        let path = if !Path::new(path).exists() && Path::new(&sealed_path).exists() { sealed_path.as_str() } else { path };
+
+        let file = File::open(path)?;
+        let mut bytes = if file.metadata()?.len() == 0 {
+            Bytes::Owned(vec![])
+        } else {
+            // Safety: inputs aren't expected to change while being solved. If one does, the worst
+            // outcome is a wrong answer or a failed UTF-8 check, as the map is only ever read.
+            Bytes::Mapped(unsafe { Mmap::map(&file)? })
+        };
+
+        if is_sealed(bytes.as_slice()) {
+            let secret = Secret::resolve(None)?;
+            bytes = Bytes::Owned(unseal_bytes(bytes.as_slice(), &secret)?);
+        }
+
+        Self::from_bytes(bytes)
+    }
+
+    #[allow(dead_code)]
+    pub fn from_string(s: String) -> Self {
+        Self { bytes: Bytes::Owned(s.into_bytes()) }
+    }
+
+    fn from_bytes(bytes: Bytes) -> Result<Self, InputError> {
+        std::str::from_utf8(bytes.as_slice()).map_err(|_| InputError::NotUtf8)?;
+        Ok(Self { bytes })
+    }
+
+    #[allow(dead_code)]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Safety: checked to be valid UTF-8 when the input was created, and never modified since.
+        unsafe { std::str::from_utf8_unchecked(self.bytes.as_slice()) }
+    }
+
+    /// Consecutive runs of whole lines of roughly `size` bytes each, see [`line_chunks`].
+    #[allow(dead_code)]
+    pub fn chunks(&self, size: usize) -> impl Iterator<Item = &str> {
+        line_chunks(self.as_str(), size)
+    }
+}
+
+impl Bytes {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Bytes::Mapped(map) => map,
+            Bytes::Owned(bytes) => bytes,
+        }
+    }
+}
+
+impl Deref for Input {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Loads a puzzle input, see [`Input::open`].
+pub(crate) fn load_input(path: String) -> Input {
+    Input::open(&path).unwrap_or_else(|e| panic!("Could not load {}: {}", path, e))
+}
+
+/// Splits `i` into consecutive chunks of at least `size` bytes, each extended to the end of the
+/// line it stops in, so no line is ever split across two chunks. Only the last may be shorter.
+pub(crate) fn line_chunks(i: &str, size: usize) -> impl Iterator<Item = &str> {
+    let size = size.max(1);
+    let mut rest = i;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let end = match rest.as_bytes().get(size..) {
+            Some(tail) => tail.iter().position(|b| *b == b'\n').map_or(rest.len(), |p| size + p + 1),
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn should_map_input_from_disk() {
+        let path = std::env::temp_dir().join(format!("aoc2023-input-{}.txt", std::process::id()));
+        fs::write(&path, "two1nine\neightwothree\n").unwrap();
+
+        let input = Input::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(input.lines().collect::<Vec<&str>>(), vec!["two1nine", "eightwothree"]);
+        assert_eq!(input.as_bytes().len(), 22);
+
+        fs::write(&path, [0xff, 0xfe]).unwrap();
+        assert!(matches!(Input::open(path.to_str().unwrap()), Err(InputError::NotUtf8)));
+
+        fs::write(&path, "").unwrap();
+        assert_eq!(Input::open(path.to_str().unwrap()).unwrap().as_str(), "");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn chunks_should_end_on_line_boundaries() {
+        let i = "aaa\nbb\ncccc\nd";
+        assert_eq!(line_chunks(i, 4).collect::<Vec<&str>>(), vec!["aaa\nbb\n", "cccc\n", "d"]);
+        assert_eq!(line_chunks(i, 1).collect::<Vec<&str>>(), vec!["aaa\n", "bb\n", "cccc\n", "d"]);
+        assert_eq!(line_chunks(i, 100).collect::<Vec<&str>>(), vec![i]);
+        assert_eq!(line_chunks("", 4).count(), 0);
+        assert_eq!(Input::from_string(i.to_owned()).chunks(4).collect::<String>(), i);
+    }
+}
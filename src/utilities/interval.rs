@@ -0,0 +1,248 @@
+//! Half-open integer ranges, normalized sets of them, and maps that shift them piece by piece.
+
+/// The values `start..end`. Arithmetic saturates at `u64::MAX` rather than overflowing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Interval {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Interval {
+    pub fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    pub fn with_length(start: u64, length: u64) -> Self {
+        Self::new(start, start.saturating_add(length))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        let overlap = Interval::new(self.start.max(other.start), self.end.min(other.end));
+        (!overlap.is_empty()).then_some(overlap)
+    }
+
+    /// The parts of `self` outside `other`: none, one or two intervals.
+    pub fn difference(&self, other: &Interval) -> Vec<Interval> {
+        let Some(overlap) = self.intersection(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+
+        [Interval::new(self.start, overlap.start), Interval::new(overlap.end, self.end)]
+            .into_iter()
+            .filter(|i| !i.is_empty())
+            .collect()
+    }
+
+    /// The parts of `self` before and from `at`, either of which may not exist.
+    #[allow(dead_code)]
+    pub fn split_at(&self, at: u64) -> (Option<Interval>, Option<Interval>) {
+        let at = at.clamp(self.start, self.end.max(self.start));
+        let before = Interval::new(self.start, at);
+        let after = Interval::new(at, self.end);
+        ((!before.is_empty()).then_some(before), (!after.is_empty()).then_some(after))
+    }
+}
+
+/// Sorted, disjoint and non-adjacent intervals; overlapping or touching ones are merged on insertion.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct RangeSet {
+    intervals: Vec<Interval>,
+}
+
+impl RangeSet {
+    pub fn new(intervals: impl IntoIterator<Item = Interval>) -> Self {
+        let mut intervals = intervals.into_iter().filter(|i| !i.is_empty()).collect::<Vec<Interval>>();
+        intervals.sort();
+
+        let mut merged: Vec<Interval> = vec![];
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => merged.push(interval),
+            }
+        }
+
+        Self { intervals: merged }
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many values the set holds.
+    #[allow(dead_code)]
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, value: u64) -> bool {
+        self.intervals.iter().any(|i| i.contains(value))
+    }
+
+    #[allow(dead_code)]
+    pub fn insert(&mut self, interval: Interval) {
+        *self = Self::new(self.intervals.iter().copied().chain([interval]));
+    }
+
+    #[allow(dead_code)]
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        Self::new(self.intervals.iter().chain(&other.intervals).copied())
+    }
+
+    #[allow(dead_code)]
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        Self::new(self.intervals.iter().flat_map(|a| other.intervals.iter().filter_map(|b| a.intersection(b))))
+    }
+
+    #[allow(dead_code)]
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        Self::new(self.intervals.iter().flat_map(|a| {
+            other.intervals.iter().fold(vec![*a], |remaining, b| {
+                remaining.iter().flat_map(|r| r.difference(b)).collect()
+            })
+        }))
+    }
+}
+
+/// A piecewise map moving each source interval so that it starts at its destination. Values outside
+/// every piece map to themselves, and where pieces overlap the first one added wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct OffsetMap {
+    pieces: Vec<(Interval, u64)>,
+}
+
+impl OffsetMap {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(dead_code)]
+    pub fn insert(&mut self, source: Interval, destination: u64) {
+        self.pieces.push((source, destination));
+    }
+
+    pub fn apply(&self, value: u64) -> u64 {
+        self.pieces.iter()
+            .find(|(source, _)| source.contains(value))
+            .map_or(value, |(source, destination)| destination.saturating_add(value - source.start))
+    }
+
+    /// Maps every value of `interval` at once, splitting it wherever it crosses the edge of a piece.
+    pub fn apply_interval(&self, interval: Interval) -> Vec<Interval> {
+        let mut unmapped = vec![interval];
+        let mut mapped = vec![];
+        for (source, destination) in self.pieces.iter() {
+            let mut remaining = vec![];
+            for part in unmapped {
+                match part.intersection(source) {
+                    Some(overlap) => {
+                        mapped.push(Interval::new(
+                            destination.saturating_add(overlap.start - source.start),
+                            destination.saturating_add(overlap.end - source.start),
+                        ));
+                        remaining.extend(part.difference(source));
+                    }
+                    None => remaining.push(part),
+                }
+            }
+            unmapped = remaining;
+        }
+
+        mapped.extend(unmapped);
+        mapped
+    }
+
+    pub fn apply_set(&self, set: &RangeSet) -> RangeSet {
+        RangeSet::new(set.intervals().iter().flat_map(|i| self.apply_interval(*i)))
+    }
+}
+
+impl FromIterator<(Interval, u64)> for OffsetMap {
+    fn from_iter<I: IntoIterator<Item = (Interval, u64)>>(pieces: I) -> Self {
+        Self { pieces: pieces.into_iter().collect() }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(intervals: &[(u64, u64)]) -> RangeSet {
+        RangeSet::new(intervals.iter().map(|(s, e)| Interval::new(*s, *e)))
+    }
+
+    #[test]
+    fn should_intersect_split_and_subtract_intervals() {
+        let a = Interval::new(10, 20);
+        assert_eq!(a.intersection(&Interval::new(15, 30)), Some(Interval::new(15, 20)));
+        assert_eq!(a.intersection(&Interval::new(20, 30)), None);
+        assert_eq!(a.difference(&Interval::new(12, 14)), vec![Interval::new(10, 12), Interval::new(14, 20)]);
+        assert_eq!(a.difference(&Interval::new(0, 30)), vec![]);
+        assert_eq!(a.split_at(15), (Some(Interval::new(10, 15)), Some(Interval::new(15, 20))));
+        assert_eq!(a.split_at(25), (Some(a), None));
+        assert_eq!(Interval::with_length(u64::MAX - 1, 5).len(), 1);
+    }
+
+    #[test]
+    fn should_normalize_range_sets() {
+        let s = set(&[(5, 8), (0, 2), (2, 3), (6, 10), (12, 12)]);
+        assert_eq!(s, set(&[(0, 3), (5, 10)]));
+        assert_eq!(s.len(), 8);
+        assert_eq!(s.min(), Some(0));
+        assert!(s.contains(9) && !s.contains(3));
+
+        let mut s = s;
+        s.insert(Interval::new(3, 5));
+        assert_eq!(s, set(&[(0, 10)]));
+        assert!(RangeSet::default().is_empty() && !s.is_empty());
+    }
+
+    #[test]
+    fn should_combine_range_sets() {
+        let a = set(&[(0, 10), (20, 30)]);
+        let b = set(&[(5, 25)]);
+        assert_eq!(a.union(&b), set(&[(0, 30)]));
+        assert_eq!(a.intersection(&b), set(&[(5, 10), (20, 25)]));
+        assert_eq!(a.difference(&b), set(&[(0, 5), (25, 30)]));
+    }
+
+    #[test]
+    fn should_apply_offset_map_to_whole_ranges() {
+        let map = [(Interval::with_length(98, 2), 50), (Interval::with_length(50, 48), 52)]
+            .into_iter()
+            .collect::<OffsetMap>();
+
+        assert_eq!(map.apply(98), 50);
+        assert_eq!(map.apply(10), 10);
+        assert_eq!(map.apply_set(&set(&[(45, 55), (97, 102)])), set(&[(45, 57), (99, 102)]));
+
+        let mut inserted = OffsetMap::new();
+        inserted.insert(Interval::with_length(98, 2), 50);
+        inserted.insert(Interval::with_length(50, 48), 52);
+        assert_eq!(inserted, map);
+    }
+}
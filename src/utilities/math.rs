@@ -0,0 +1,144 @@
+//! Number theory for puzzles built on repeating cycles. Anything that could overflow returns `None`.
+
+pub(crate) fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[allow(dead_code)]
+pub(crate) fn lcm(a: u64, b: u64) -> Option<u64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+#[allow(dead_code)]
+pub(crate) fn gcd_all(values: &[u64]) -> u64 {
+    values.iter().fold(0, |acc, v| gcd(acc, *v))
+}
+
+/// The lowest common multiple of every value, or 1 when there are none.
+#[allow(dead_code)]
+pub(crate) fn lcm_all(values: &[u64]) -> Option<u64> {
+    values.iter().try_fold(1, |acc, v| lcm(acc, *v))
+}
+
+/// `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`.
+pub(crate) fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a.abs(), a.signum(), 0);
+    }
+
+    let (g, x, y) = extended_gcd(b, a.rem_euclid(b));
+    (g, y, x - a.div_euclid(b) * y)
+}
+
+/// `x` such that `a * x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+#[allow(dead_code)]
+pub(crate) fn mod_inverse(a: u64, m: u64) -> Option<u64> {
+    if m == 0 {
+        return None;
+    }
+
+    let (g, x, _) = extended_gcd(a as i128, m as i128);
+    (g == 1).then(|| x.rem_euclid(m as i128) as u64)
+}
+
+/// Solves `x ≡ r (mod m)` for every `(r, m)` at once, returning `(x, lcm of the moduli)` with `x`
+/// the smallest non-negative solution. The moduli needn't be coprime; congruences that contradict
+/// each other have no solution.
+pub(crate) fn crt(congruences: &[(u64, u64)]) -> Option<(u64, u64)> {
+    let mut residue: i128 = 0;
+    let mut modulus: i128 = 1;
+    for (r, m) in congruences.iter() {
+        let (r, m) = (*r as i128, *m as i128);
+        if m == 0 {
+            return None;
+        }
+
+        let (g, p, _) = extended_gcd(modulus, m);
+        let difference = r - residue;
+        if difference.rem_euclid(g) != 0 {
+            return None;
+        }
+
+        let combined = modulus.checked_mul(m / g)?;
+        let step = (difference / g).rem_euclid(m / g).checked_mul(p.rem_euclid(m / g))?.rem_euclid(m / g);
+        residue = (residue + modulus.checked_mul(step)?).rem_euclid(combined);
+        modulus = combined;
+    }
+
+    Some((u64::try_from(residue).ok()?, u64::try_from(modulus).ok()?))
+}
+
+/// The natural log of `n` choose `k`, built up one exact factor at a time so it stays finite far
+/// beyond where the binomial itself would overflow. Negative infinity when `k > n`.
+pub(crate) fn ln_binomial(n: u64, k: u64) -> f64 {
+    if k > n {
+        return f64::NEG_INFINITY;
+    }
+
+    let k = k.min(n - k);
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_find_gcd_and_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd_all(&[12, 18, 8]), 2);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm_all(&[2, 3, 4, 5]), Some(60));
+        assert_eq!(lcm_all(&[]), Some(1));
+    }
+
+    #[test]
+    fn lcm_should_report_overflow() {
+        assert_eq!(lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(lcm_all(&[u64::MAX, 2]), None);
+    }
+
+    #[test]
+    fn should_find_bezout_coefficients() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn should_find_modular_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(6, 9), None);
+    }
+
+    #[test]
+    fn should_solve_congruences() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(3, 4), (1, 6)]), Some((7, 12)));
+        assert_eq!(crt(&[(0, 4), (1, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn crt_should_match_search() {
+        for (a, m) in [(1u64, 4u64), (3, 6), (5, 9)] {
+            for (b, n) in [(2u64, 10u64), (0, 15), (4, 8)] {
+                let expected = (0..m * n).find(|x| x % m == a && x % n == b);
+                assert_eq!(crt(&[(a, m), (b, n)]).map(|(x, _)| x), expected, "{} mod {}, {} mod {}", a, m, b, n);
+            }
+        }
+    }
+
+    #[test]
+    fn should_find_log_binomials() {
+        assert_eq!(ln_binomial(7, 0), 0.0);
+        assert!((ln_binomial(5, 2).exp() - 10.0).abs() < 1e-9);
+        assert!((ln_binomial(52, 5).exp() - 2_598_960.0).abs() < 1e-3);
+        assert!(ln_binomial(1000, 500).is_finite());
+        assert_eq!(ln_binomial(2, 3), f64::NEG_INFINITY);
+    }
+}
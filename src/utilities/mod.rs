@@ -0,0 +1,48 @@
+pub(crate) mod graph;
+pub(crate) mod grid;
+pub(crate) mod input;
+pub(crate) mod interval;
+pub(crate) mod math;
+pub(crate) mod parsing;
+
+use std::path::Path;
+use crate::seal::SEALED_EXTENSION;
+
+/// Whether an input exists at `path`, either as plaintext or sealed.
+pub(crate) fn input_exists(path: &str) -> bool {
+    Path::new(path).exists() || Path::new(&format!("{}{}", path, SEALED_EXTENSION)).exists()
+}
+
+/// A cheap, non-cryptographic fingerprint of some bytes. It's FNV-1a rather than `DefaultHasher` because
+/// fingerprints are written to disk, so they have to stay the same across builds and toolchains.
+pub(crate) fn fingerprint(bytes: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, bytes)
+}
+
+/// Fingerprints several byte strings as one. Each is prefixed with its length, so bytes moving from one
+/// part to the next still change the result.
+pub(crate) fn fingerprint_all<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u64 {
+    parts.into_iter().fold(FNV_OFFSET_BASIS, |hash, part| {
+        fnv1a(fnv1a(hash, &(part.len() as u64).to_le_bytes()), part)
+    })
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, b| (hash ^ *b as u64).wrapping_mul(FNV_PRIME))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fingerprints_should_be_stable() {
+        assert_eq!(fingerprint(b""), 0xcbf29ce484222325);
+        assert_eq!(fingerprint(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fingerprint(b"foobar"), 0x85944171f73967e8);
+        assert_ne!(fingerprint_all([b"ab".as_slice(), b"c"]), fingerprint_all([b"a".as_slice(), b"bc"]));
+    }
+}
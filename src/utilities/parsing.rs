@@ -0,0 +1,115 @@
+//! Small nom combinators for the shapes puzzle inputs keep coming in.
+
+use std::str::FromStr;
+use nom::bytes::complete::tag;
+use nom::character::complete::{digit1, line_ending, space0, space1};
+use nom::combinator::{map_res, recognize, verify};
+use nom::error::{Error, ErrorKind};
+use nom::multi::{many1, separated_list1};
+use nom::sequence::{pair, preceded, separated_pair, tuple};
+use nom::{IResult, Parser};
+
+/// An unsigned integer of whatever width the caller asks for.
+pub(crate) fn number<T: FromStr>(i: &str) -> IResult<&str, T> {
+    map_res(digit1, str::parse)(i)
+}
+
+/// Integers separated by runs of spaces, e.g. `41 48  6 31`, skipping any leading spaces.
+pub(crate) fn numbers<T: FromStr>(i: &str) -> IResult<&str, Vec<T>> {
+    preceded(space0, separated_list1(space1, number))(i)
+}
+
+/// Space-separated groups of digits read as a single number, e.g. `7  15   30` as `71530`.
+/// Fails with `TooLarge` when the combined number doesn't fit in `T`.
+pub(crate) fn spaced_digits<T: FromStr>(i: &str) -> IResult<&str, T> {
+    let (rest, groups) = preceded(space0, separated_list1(space1, digit1))(i)?;
+    match groups.concat().parse() {
+        Ok(n) => Ok((rest, n)),
+        Err(_) => Err(nom::Err::Error(Error::new(i, ErrorKind::TooLarge))),
+    }
+}
+
+/// A value introduced by a label, e.g. `labelled("Time:", numbers)` for `Time:  7  15  30`.
+pub(crate) fn labelled<'a, O, F>(label: &'a str, value: F) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where
+    F: Parser<&'a str, O, Error<&'a str>>,
+{
+    preceded(pair(tag(label), space0), value)
+}
+
+/// A key and a value either side of a separator that may be padded with spaces, e.g. `AAA = (BBB, CCC)`.
+pub(crate) fn key_value<'a, K, V, FK, FV>(key: FK, separator: &'a str, value: FV) -> impl FnMut(&'a str) -> IResult<&'a str, (K, V)>
+where
+    FK: Parser<&'a str, K, Error<&'a str>>,
+    FV: Parser<&'a str, V, Error<&'a str>>,
+{
+    separated_pair(key, tuple((space0, tag(separator), space0)), value)
+}
+
+/// One `item` per line. A trailing line break is left unconsumed.
+pub(crate) fn lines<'a, O, F>(item: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where
+    F: Parser<&'a str, O, Error<&'a str>>,
+{
+    separated_list1(line_ending, item)
+}
+
+/// A line break followed by at least one empty (or whitespace-only) line.
+pub(crate) fn blank_line(i: &str) -> IResult<&str, &str> {
+    recognize(pair(line_ending, many1(pair(space0, line_ending))))(i)
+}
+
+/// Sections of the input separated by blank lines, e.g. the maps of an almanac.
+pub(crate) fn blocks<'a, O, F>(block: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where
+    F: Parser<&'a str, O, Error<&'a str>>,
+{
+    separated_list1(blank_line, block)
+}
+
+/// Rows of cells, one row per line, failing unless every row is the same width.
+pub(crate) fn grid<'a, O, F>(cell: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<Vec<O>>>
+where
+    F: Parser<&'a str, O, Error<&'a str>>,
+{
+    verify(lines(many1(cell)), |rows: &Vec<Vec<O>>| rows.iter().all(|r| r.len() == rows[0].len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nom::character::complete::{alpha1, none_of};
+
+    #[test]
+    fn should_parse_space_separated_numbers() {
+        assert_eq!(numbers::<u32>("  41 48  6 | 83"), Ok((" | 83", vec![41, 48, 6])));
+        assert_eq!(numbers::<u8>("300"), Err(nom::Err::Error(Error::new("300", ErrorKind::MapRes))));
+        assert!(numbers::<u32>("x").is_err());
+    }
+
+    #[test]
+    fn should_join_spaced_digits() {
+        assert_eq!(spaced_digits::<u64>(" 7  15   30\n"), Ok(("\n", 71530)));
+        assert!(spaced_digits::<u8>("25 6").is_err());
+    }
+
+    #[test]
+    fn should_parse_labelled_and_key_value_lines() {
+        assert_eq!(labelled("Time:", numbers::<u32>)("Time:   7  15"), Ok(("", vec![7, 15])));
+        assert!(labelled("Time:", numbers::<u32>)("Distance: 9").is_err());
+        assert_eq!(key_value(alpha1, "=", alpha1)("AAA = BBB"), Ok(("", ("AAA", "BBB"))));
+        assert_eq!(key_value(alpha1, ":", number::<u32>)("red:12"), Ok(("", ("red", 12))));
+    }
+
+    #[test]
+    fn should_split_lines_and_blocks() {
+        assert_eq!(lines(numbers::<u32>)("1 2\n3\n"), Ok(("\n", vec![vec![1, 2], vec![3]])));
+        assert_eq!(blocks(lines(number::<u32>))("1\n2\n\n3\n \n\n4"), Ok(("", vec![vec![1, 2], vec![3], vec![4]])));
+    }
+
+    #[test]
+    fn should_reject_ragged_grids() {
+        assert_eq!(grid(none_of("\r\n"))("ab\ncd"), Ok(("", vec![vec!['a', 'b'], vec!['c', 'd']])));
+        assert!(grid(none_of("\r\n"))("ab\nc").is_err());
+    }
+}